@@ -0,0 +1,98 @@
+/// Walker/Vose alias table: O(1) sampling of an index w. probability proportional to its weight.
+///
+/// Each of the n columns holds its own index w. probability `prob[i]`, and `alias[i]` otherwise,
+/// so a sample is one column pick and one coin flip. Both come from a single uniform number.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct AliasTable {
+    prob: Vec<f32>,
+    alias: Vec<usize>
+}
+
+impl AliasTable {
+    /// `weights` - non-negative, don't have to be normalized. All zeros - uniform.
+    pub(crate) fn new(weights: &[f32]) -> Self {
+        assert!(!weights.is_empty());
+
+        let n = weights.len();
+        let total: f64 = weights.iter().map(|w| w.max(0.0) as f64).sum();
+
+        // scaled so that the average column is exactly 1.
+        let mut scaled: Vec<f64> = if total > 0.0 {
+            weights.iter().map(|w| w.max(0.0) as f64 * n as f64 / total).collect()
+        } else {
+            vec![1.0; n]
+        };
+
+        let mut prob = vec![1.0f32; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..n).partition(|&i| scaled[i] < 1.0);
+
+        while let (Some(&lo), Some(&hi)) = (small.last(), large.last()) {
+            small.pop();
+
+            // lo's column is topped up by hi.
+            prob[lo] = scaled[lo] as f32;
+            alias[lo] = hi;
+
+            scaled[hi] -= 1.0 - scaled[lo];
+            if scaled[hi] < 1.0 {
+                large.pop();
+                small.push(hi);
+            }
+        }
+
+        // leftovers are 1, up to rounding - keep their own index.
+        Self { prob, alias }
+    }
+
+    pub(crate) fn len(&self) -> usize { self.prob.len() }
+
+    /// `r` - uniform in [0..1).
+    pub(crate) fn sample(&self, r: f32) -> usize {
+        let n = self.prob.len();
+        let scaled = r * n as f32;
+        let column = (scaled as usize).min(n - 1);
+
+        if scaled - (column as f32) < self.prob[column] {
+            column
+        } else {
+            self.alias[column]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modnar::Modnar;
+    use super::AliasTable;
+
+    fn frequencies(table: &AliasTable, samples: usize) -> Vec<f32> {
+        let mut rnd = Modnar::new_rng_seeded(42);
+        let mut counts = vec![0usize; table.len()];
+
+        for _ in 0..samples {
+            counts[table.sample(rnd.gen_f32())] += 1;
+        }
+
+        counts.into_iter().map(|count| count as f32 / samples as f32).collect()
+    }
+
+    #[test]
+    fn test_frequencies_match_weights() {
+        let weights = [0.1, 3.0, 0.0, 1.4, 0.5];
+        let total: f32 = weights.iter().sum();
+        let freq = frequencies(&AliasTable::new(&weights), 1_000_000);
+
+        for (weight, freq) in weights.iter().zip(freq.iter()) {
+            assert!((weight / total - freq).abs() < 0.005, "{} vs {freq}", weight / total);
+        }
+        assert_eq!(freq[2], 0.0);
+    }
+
+    #[test]
+    fn test_degenerate_weights() {
+        assert!(frequencies(&AliasTable::new(&[0.0, 0.0]), 10_000).iter().all(|freq| (freq - 0.5).abs() < 0.05));
+        assert_eq!(frequencies(&AliasTable::new(&[2.0]), 100), vec![1.0]);
+    }
+}
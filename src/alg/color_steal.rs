@@ -0,0 +1,199 @@
+use image::RgbImage;
+use crate::ds::palette::Palette;
+
+// normalized [0..1] image coordinates.
+type Vertex = (f32, f32);
+
+/// How colors are stolen from an image.
+pub(crate) enum StealMethod {
+    /// Walks a polyline (vertices are in normalized [0..1] image coordinates)
+    /// and samples `Palette::SIZE` colors, evenly spaced by arc length.
+    /// Each sample is an average over (2*radius + 1)^2 pixels around the point.
+    Path { vertices: Vec<Vertex>, radius: u32 },
+    /// K-means over the pixels. Centroids are sorted by luminance, and each one
+    /// gets a share of the palette proportional to the size of its cluster.
+    Clusters { k: usize, iterations: u8 }
+}
+
+impl StealMethod {
+    /// Top left to bottom right corner.
+    pub(crate) fn diagonal() -> Self {
+        Self::Path { vertices: vec![(0.0, 0.0), (1.0, 1.0)], radius: 1 }
+    }
+}
+
+pub(crate) struct ColorSteal;
+
+impl ColorSteal {
+    // Upper bound on the pixels k-means looks at.
+    const MAX_CLUSTER_SAMPLES: usize = 50_000;
+
+    pub(crate) fn steal(name: &str, img: &RgbImage, method: &StealMethod) -> Palette {
+        assert!(img.width() > 0 && img.height() > 0);
+
+        match method {
+            StealMethod::Path { vertices, radius } => Self::along_path(name, img, vertices, *radius),
+            StealMethod::Clusters { k, iterations } => Self::by_clustering(name, img, *k, *iterations)
+        }
+    }
+
+    fn along_path(name: &str, img: &RgbImage, vertices: &[Vertex], radius: u32) -> Palette {
+        assert!(!vertices.is_empty());
+
+        let segments: Vec<(Vertex, Vertex, f32)> = vertices
+            .windows(2)
+            .map(|pair| (pair[0], pair[1], ((pair[1].0 - pair[0].0).powi(2) + (pair[1].1 - pair[0].1).powi(2)).sqrt()))
+            .collect();
+        let total_len: f32 = segments.iter().map(|segment| segment.2).sum();
+
+        let colors = (0..Palette::SIZE).map(|idx| {
+            let mut dist = total_len * idx as f32 / (Palette::SIZE - 1) as f32;
+            let mut point = vertices[vertices.len() - 1];
+
+            for (from, to, len) in segments.iter() {
+                if dist <= *len {
+                    let t = if *len > f32::EPSILON { dist / len } else { 0.0 };
+                    point = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+                    break;
+                }
+                dist -= len;
+            }
+
+            Self::average_around(img, point, radius)
+        }).collect();
+
+        Palette::new(name, colors)
+    }
+
+    fn average_around(img: &RgbImage, point: Vertex, radius: u32) -> [f32; 3] {
+        let max_x = img.width() - 1;
+        let max_y = img.height() - 1;
+        let cx = (point.0.clamp(0.0, 1.0) * max_x as f32).round() as u32;
+        let cy = (point.1.clamp(0.0, 1.0) * max_y as f32).round() as u32;
+
+        let mut sum = [0.0f32; 3];
+        let mut cnt = 0.0;
+
+        for x in cx.saturating_sub(radius)..=(cx + radius).min(max_x) {
+            for y in cy.saturating_sub(radius)..=(cy + radius).min(max_y) {
+                let pixel = img.get_pixel(x, y);
+                for channel in 0..3 {
+                    sum[channel] += pixel[channel] as f32 / 255.0;
+                }
+                cnt += 1.0;
+            }
+        }
+
+        sum.map(|channel| channel / cnt)
+    }
+
+    fn by_clustering(name: &str, img: &RgbImage, k: usize, iterations: u8) -> Palette {
+        assert!(k > 0);
+
+        let total = (img.width() * img.height()) as usize;
+        let step = (total / Self::MAX_CLUSTER_SAMPLES).max(1);
+        let samples: Vec<[f32; 3]> = img
+            .pixels()
+            .step_by(step)
+            .map(|pixel| [pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0])
+            .collect();
+
+        // deterministic init: luminance quantiles.
+        let mut sorted = samples.clone();
+        sorted.sort_by(|lhs, rhs| Self::luminance(lhs).total_cmp(&Self::luminance(rhs)));
+        let k = k.min(sorted.len());
+        let mut centroids: Vec<[f32; 3]> = (0..k)
+            .map(|idx| sorted[(2 * idx + 1) * sorted.len() / (2 * k)])
+            .collect();
+        let mut sizes = vec![0usize; k];
+
+        for _ in 0..iterations.max(1) {
+            let mut sums = vec![[0.0f32; 3]; k];
+            sizes = vec![0usize; k];
+
+            for sample in samples.iter() {
+                let closest = Self::closest(&centroids, sample);
+                sizes[closest] += 1;
+                for channel in 0..3 {
+                    sums[closest][channel] += sample[channel];
+                }
+            }
+
+            for idx in 0..k {
+                if sizes[idx] > 0 {
+                    centroids[idx] = sums[idx].map(|channel| channel / sizes[idx] as f32);
+                }
+            }
+        }
+
+        let mut clusters: Vec<([f32; 3], usize)> = centroids
+            .into_iter()
+            .zip(sizes)
+            .filter(|(_, size)| *size > 0)
+            .collect();
+        clusters.sort_by(|lhs, rhs| Self::luminance(&lhs.0).total_cmp(&Self::luminance(&rhs.0)));
+
+        // each centroid sits in the middle of its share of the palette.
+        let total: usize = clusters.iter().map(|(_, size)| size).sum();
+        let mut start = 0.0;
+        let stops: Vec<(f32, [f32; 3])> = clusters
+            .iter()
+            .map(|(rgb, size)| {
+                let share = *size as f32 / total as f32;
+                let stop = (start + share / 2.0, *rgb);
+                start += share;
+                stop
+            })
+            .collect();
+
+        Palette::from_stops(name, &stops)
+    }
+
+    fn closest(centroids: &[[f32; 3]], sample: &[f32; 3]) -> usize {
+        let dst = |centroid: &[f32; 3]| {
+            (0..3).map(|channel| (centroid[channel] - sample[channel]).powi(2)).sum::<f32>()
+        };
+
+        centroids
+            .iter()
+            .enumerate()
+            .min_by(|lhs, rhs| dst(lhs.1).total_cmp(&dst(rhs.1)))
+            .map(|(idx, _)| idx)
+            .unwrap()
+    }
+
+    fn luminance(rgb: &[f32; 3]) -> f32 {
+        0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+    use super::{ColorSteal, StealMethod};
+
+    // left half black, right half white.
+    fn two_tone() -> RgbImage {
+        RgbImage::from_fn(16, 16, |x, _| if x < 8 { Rgb([0, 0, 0]) } else { Rgb([255, 255, 255]) })
+    }
+
+    #[test]
+    fn test_steal_along_path() {
+        let method = StealMethod::Path { vertices: vec![(0.0, 0.5), (1.0, 0.5)], radius: 0 };
+        let palette = ColorSteal::steal("path", &two_tone(), &method);
+
+        assert_eq!(palette.lookup(0.0), [0.0, 0.0, 0.0]);
+        assert_eq!(palette.lookup(1.0), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_steal_by_clustering() {
+        let method = StealMethod::Clusters { k: 2, iterations: 5 };
+        let palette = ColorSteal::steal("clusters", &two_tone(), &method);
+
+        // dark cluster first, both clusters are of the same size.
+        assert_eq!(palette.lookup(0.0), [0.0, 0.0, 0.0]);
+        assert_eq!(palette.lookup(1.0), [1.0, 1.0, 1.0]);
+        assert!((palette.lookup(0.5)[0] - 0.5).abs() < 0.01);
+    }
+}
@@ -89,6 +89,32 @@ impl Combinations {
         result
     }
 
+    /// Number of tuples of `draw_sz` elements, each drawn from the pool (repetitions allowed, order matters).
+    /// Pool isn't limited to 255 elements (unlike combinations), i.e. it's all the described mutators.
+    pub(crate) fn tuples(pool_sz: usize, draw_sz: u8) -> u64 {
+        (pool_sz as u64).checked_pow(draw_sz as u32).expect("Arithmetic overflow!")
+    }
+
+    /// Returns a tuple, using its lexicographical rank (i.e. digits of `rank - 1` in base `pool_sz`).
+    /// Smallest rank is 1, indicies of elements in pool start w. 1 (same as `unrank`).
+    pub(crate) fn unrank_tuple(rank: u64, pool_sz: usize, draw_sz: u8) -> Vec<usize> {
+        assert!(rank >= 1 && rank <= Self::tuples(pool_sz, draw_sz));
+
+        let mut r = rank - 1;
+        let mut tuple = vec![0usize; draw_sz as usize];
+
+        for element in tuple.iter_mut().rev() {
+            *element = (r % pool_sz as u64) as usize + 1;
+            r /= pool_sz as u64;
+        }
+
+        tuple
+    }
+
+    pub(crate) fn rank_tuple(tuple: &[usize], pool_sz: usize) -> u64 {
+        tuple.iter().fold(0, |acc, element| acc * pool_sz as u64 + (*element - 1) as u64) + 1
+    }
+
     fn key(n: u8, k: u8) -> usize {
         let n = n as u16;
         let k = k as u16;
@@ -227,4 +253,22 @@ mod tests {
         eprintln!("{:}", rank);
     }
 
+    #[test]
+    fn test_rank_unrank_tuple() {
+        assert_eq!(Combinations::tuples(3, 2), 9);
+        assert_eq!(Combinations::unrank_tuple(1, 3, 2), vec![1, 1]);
+        assert_eq!(Combinations::unrank_tuple(6, 3, 2), vec![2, 3]);
+        assert_eq!(Combinations::unrank_tuple(9, 3, 2), vec![3, 3]);
+
+        (1..=Combinations::tuples(48, 3)).step_by(997).for_each(|rank| {
+            assert_eq!(Combinations::rank_tuple(&Combinations::unrank_tuple(rank, 48, 3), 48), rank);
+        });
+
+        // more than u8 can count, i.e. builtin and expression mutators together.
+        assert_eq!(Combinations::tuples(300, 4), 300u64.pow(4));
+        assert_eq!(Combinations::unrank_tuple(Combinations::tuples(300, 4), 300, 4), vec![300; 4]);
+        assert_eq!(Combinations::unrank_tuple(300 * 299 + 1, 300, 4), vec![1, 1, 300, 1]);
+        assert_eq!(Combinations::rank_tuple(&[1, 1, 300, 1], 300), 300 * 299 + 1);
+    }
+
 }
\ No newline at end of file
@@ -1,2 +1,4 @@
 pub(crate) mod combinations;
-pub(crate) mod big_range_random_cursor;
\ No newline at end of file
+pub(crate) mod big_range_random_cursor;
+pub(crate) mod color_steal;
+pub(crate) mod alias_table;
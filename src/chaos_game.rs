@@ -1,114 +1,436 @@
+use std::borrow::Cow;
+use crate::alg::alias_table::AliasTable;
 use crate::ds::aff_ifs::AffIfs;
+use crate::ds::color_histogram::ColorHistogram;
 use crate::ds::ifs_transform::IfsTransform;
-use crate::ds::point::Point;
+use crate::ds::point::{ColoredPoint, Point};
+use crate::ds::xaos::Xaos;
 use crate::modnar::Modnar;
-use crate::mutators::{apply_mutator_combination, MutatorConfig};
+use crate::statistics::auto_frame::AutoFrame;
+use crate::statistics::camera::Camera;
 
 pub(crate) struct ChaosGame {
     rnd: Modnar,
-    lsfr: Modnar
+    lsfr: Modnar,
+    stats: RunStats
+}
+
+/// Of a run of the chaos game.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct RunStats {
+    pub(crate) iterations: u64,
+    /// Points, which went non-finite or out of bounds, and were reseeded.
+    pub(crate) bad_values: u64
+}
+
+impl RunStats {
+    pub(crate) fn bad_values_ratio(&self) -> f32 {
+        self.bad_values as f32 / self.iterations.max(1) as f32
+    }
+
+    pub(crate) fn merge(&mut self, other: &RunStats) {
+        self.iterations += other.iterations;
+        self.bad_values += other.bad_values;
+    }
 }
 
 pub(crate) trait AffineTransformProvider {
-    fn find_transform(&self, prob: f32) -> Option<&IfsTransform>;
+    /// `p` of each transform is its weight (doesn't have to be normalized).
+    fn transforms(&self) -> &[IfsTransform];
+
+    /// Applied to each point before it's plotted, doesn't feed back into the iteration.
+    fn final_transform(&self) -> Option<&IfsTransform> { None }
+
+    fn xaos(&self) -> Option<&Xaos> { None }
+
+    /// Prebuilt one, none - it's built for each run.
+    fn selector(&self) -> Option<&TransformSelector> { None }
+
+    fn selector_or_new(&self) -> Cow<'_, TransformSelector> where Self: Sized {
+        match self.selector() {
+            Some(selector) => Cow::Borrowed(selector),
+            None => Cow::Owned(TransformSelector::new(self))
+        }
+    }
 }
 
 impl AffineTransformProvider for Vec<IfsTransform> {
-    fn find_transform(&self, prob: f32) -> Option<&IfsTransform> {
-        self.iter()
-            .find(|t| prob <= t.p)
+    fn transforms(&self) -> &[IfsTransform] {
+        self
+    }
+}
+
+impl AffineTransformProvider for AffIfs {
+    fn transforms(&self) -> &[IfsTransform] {
+        &self.transforms
+    }
+
+    fn final_transform(&self) -> Option<&IfsTransform> {
+        self.final_transform.as_ref()
     }
+
+    fn xaos(&self) -> Option<&Xaos> {
+        self.xaos.as_ref()
+    }
+
+    fn selector(&self) -> Option<&TransformSelector> {
+        self.selector.as_ref()
+    }
+}
+
+/// Picks the next transform in O(1): alias table of the weights,
+/// w. xaos - also one per previously picked transform.
+/// Built once per IFS, see `AffIfs::selector`.
+#[derive(Clone, Debug)]
+pub(crate) struct TransformSelector {
+    first: AliasTable,
+    after: Vec<AliasTable>
 }
 
-impl AffineTransformProvider for &AffIfs {
-    fn find_transform(&self, prob: f32) -> Option<&IfsTransform> {
-        self.transforms
+impl TransformSelector {
+    pub(crate) fn new(aff_t_provider: &impl AffineTransformProvider) -> Self {
+        let weights: Vec<f32> = aff_t_provider
+            .transforms()
             .iter()
-            .find(|t| prob <= t.p)
+            .map(|transform| transform.p)
+            .collect();
+
+        let after = match aff_t_provider.xaos() {
+            Some(xaos) => (0..weights.len())
+                .map(|from| AliasTable::new(&xaos.row(from, &weights)))
+                .collect(),
+            None => Vec::new()
+        };
+
+        Self { first: AliasTable::new(&weights), after }
+    }
+
+    /// Index of the transform, `prev` - the previously picked one (none at the start), `r` - uniform in [0..1).
+    pub(crate) fn pick(&self, prev: Option<usize>, r: f32) -> usize {
+        match prev {
+            Some(prev) if !self.after.is_empty() => self.after[prev].sample(r),
+            _ => self.first.sample(r)
+        }
     }
 }
 
 impl ChaosGame {
+    // iterations, which aren't plotted at the start (and after reseeding), while the point converges onto the attractor.
+    const FUSE: u32 = 20;
+    // same as flam3's.
+    const BAD_VALUE_BOUND: f32 = 1e10;
+
     pub(crate) fn new() -> Self {
-        Self { rnd: Modnar::new_rng(),lsfr:  Modnar::new_lsfr(7) }
+        Self { rnd: Modnar::new_rng(),lsfr:  Modnar::new_lsfr(7), stats: RunStats::default() }
     }
 
+    pub(crate) fn new_seeded(seed: u64) -> Self {
+        Self { rnd: Modnar::new_rng_seeded(seed), lsfr: Modnar::new_lsfr(7), stats: RunStats::default() }
+    }
+
+    /// Of the last run, i.e. a high bad values ratio means the genome is degenerate.
+    pub(crate) fn stats(&self) -> RunStats { self.stats }
+
     pub(crate) fn run_chaos_game(
         &mut self,
         aff_t_provider: &impl AffineTransformProvider,
-        mutators: Option<&[MutatorConfig]>,
         iterations: u32
     ) -> Vec<f32> {
         let mut res = Vec::<f32>::new();
-        self.run_chaos_game_(aff_t_provider, mutators, iterations, |point| {
-            res.push(point.x);
-            res.push(point.y);
+        self.run_chaos_game_(aff_t_provider, iterations, |colored| {
+            res.push(colored.point.x);
+            res.push(colored.point.y);
         });
 
         res
     }
 
+    /// Same as `run_chaos_game`, but also returns color coordinate of each sample.
+    /// Returns (samples, colors), `colors[i]` corresponds to `samples[2*i], samples[2*i+1]`.
+    pub(crate) fn run_chaos_game_colored(
+        &mut self,
+        aff_t_provider: &impl AffineTransformProvider,
+        iterations: u32
+    ) -> (Vec<f32>, Vec<f32>) {
+        let mut samples = Vec::<f32>::new();
+        let mut colors = Vec::<f32>::new();
+        self.run_chaos_game_(aff_t_provider, iterations, |colored| {
+            samples.push(colored.point.x);
+            samples.push(colored.point.y);
+            colors.push(colored.color);
+        });
+
+        (samples, colors)
+    }
+
+    /// Points go straight into the histogram (of the camera's size), points out of view are discarded.
+    /// Memory is bounded by the histogram size, not by the number of iterations.
+    pub(crate) fn run_chaos_game_streaming(
+        &mut self,
+        aff_t_provider: &impl AffineTransformProvider,
+        iterations: u32,
+        camera: &Camera
+    ) -> ColorHistogram {
+        let mut hist = ColorHistogram::new(camera.width, camera.height);
+        self.run_chaos_game_(aff_t_provider, iterations, |colored| {
+            if let Some(index) = camera.bin(colored.point.x, colored.point.y) {
+                hist.plot(index, colored.color);
+            }
+        });
+
+        hist
+    }
+
+    /// Camera, auto-framed to a short run of the chaos game.
+    /// Used when there is no camera for `run_chaos_game_streaming`.
+    pub(crate) fn pilot_camera(
+        &mut self,
+        aff_t_provider: &impl AffineTransformProvider,
+        iterations: u32,
+        frame: &AutoFrame,
+        width: usize,
+        height: usize
+    ) -> Camera {
+        let samples = self.run_chaos_game(aff_t_provider, iterations);
+        frame.frame(&samples, width, height)
+    }
+
     fn run_chaos_game_<F>(
         &mut self,
         aff_t_provider: &impl AffineTransformProvider,
-        mutators: Option<&[MutatorConfig]>,
         iterations: u32,
         mut point_visitor: F
     )
     where
-        F: FnMut(&Point) -> (),
+        F: FnMut(&ColoredPoint) -> (),
     {
-        let mut point: Point = Point::zero();
+        let mut colored = ColoredPoint::new(Point::zero(), self.rnd.gen_f32());
+        let selector = aff_t_provider.selector_or_new();
+        let transforms = aff_t_provider.transforms();
+        let final_transform = aff_t_provider.final_transform();
+        let mut prev = None;
+        let mut fuse = Self::FUSE;
+        self.stats = RunStats { iterations: iterations as u64, bad_values: 0 };
 
-        for i in 1..=iterations {
+        for _ in 1..=iterations {
             let r: f32 = self.rnd.gen_f32();
-            let transform = aff_t_provider
-                .find_transform(r)
-                .expect("Didn't find transform!");
+            let idx = selector.pick(prev, r);
+            let transform = &transforms[idx];
+            prev = Some(idx);
 
-            let mat = &transform.mat;
-            point.transform(mat);
+            colored.point = transform.apply(&colored.point, &mut self.rnd);
+            colored.color = transform.blend_color(colored.color);
 
-            if let Some(mutators) = mutators {
-                point = apply_mutator_combination(mutators, &point, mat, &mut self.rnd);
+            // poisoned points would stay poisoned - start over, w. a fresh fuse.
+            if Self::is_bad(&colored.point) {
+                self.stats.bad_values += 1;
+                colored.point = Point::new(2.0*self.rnd.gen_f32() - 1.0, 2.0*self.rnd.gen_f32() - 1.0);
+                fuse = Self::FUSE;
+                continue;
             }
 
-            if i <= 20 {
+            if fuse > 0 {
+                fuse -= 1;
                 continue;
             }
-            
-            point_visitor(&point);
+
+            match final_transform {
+                Some(final_transform) => {
+                    let plotted = ColoredPoint::new(
+                        final_transform.apply(&colored.point, &mut self.rnd),
+                        final_transform.blend_color(colored.color)
+                    );
+
+                    // doesn't feed back, so nothing to reseed.
+                    if Self::is_bad(&plotted.point) {
+                        self.stats.bad_values += 1;
+                    } else {
+                        point_visitor(&plotted);
+                    }
+                },
+                None => point_visitor(&colored)
+            }
         }
     }
+
+    // non-finite, or so far away it's going to be.
+    fn is_bad(point: &Point) -> bool {
+        !(point.x.abs() <= Self::BAD_VALUE_BOUND && point.y.abs() <= Self::BAD_VALUE_BOUND)
+    }
+
     // - TODO: refactor.
     pub(crate) fn run_convergence_test(
         &mut self,
-        aff_t_provider: &impl AffineTransformProvider,
-        mutators: Option<&[MutatorConfig]>
+        aff_t_provider: &impl AffineTransformProvider
     ) -> bool {
         let mut nw = Point::new(-1.0, 1.0);
         let mut se = Point::new(1.0, -1.0);
 
         const ITERATIONS: u8 = 30;
+        let selector = aff_t_provider.selector_or_new();
+        let transforms = aff_t_provider.transforms();
+        let mut prev = None;
 
         for i in 1..=ITERATIONS {
             let r: f32 = self.lsfr.gen_f32();
 
             // select transform at random
-            let transform = aff_t_provider
-                .find_transform(r)
-                .expect("Didn't find transform!");
-
-            let mat = &transform.mat;
-            nw.transform(mat);
-            se.transform(mat);
+            let idx = selector.pick(prev, r);
+            let transform = &transforms[idx];
+            prev = Some(idx);
 
-            if let Some(mutators) = mutators {
-                nw = apply_mutator_combination(mutators, &nw, mat, &mut self.rnd);
-                se = apply_mutator_combination(mutators, &se, mat, &mut self.rnd);
-            }
+            nw = transform.apply(&nw, &mut self.rnd);
+            se = transform.apply(&se, &mut self.rnd);
         }
 
         nw.dst_fast(&se) <= std::f32::consts::SQRT_2 * 0.1
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::aff_ifs::{AffIfs, ChaosGamePreprocess};
+    use crate::ds::ifs_transform::IfsTransform;
+    use crate::ds::xaos::Xaos;
+    use crate::statistics::auto_frame::AutoFrame;
+    use crate::statistics::camera::Camera;
+    use crate::modnar::Modnar;
+    use crate::mutators::MutatorConfig;
+    use crate::statistics::plane::Range2D;
+    use super::{ChaosGame, TransformSelector};
+
+    fn gasket() -> Vec<IfsTransform> {
+        let mut ifs = vec![
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.33, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 0.33, 0.5, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.5), 0.33, 1.0, 0.5),
+        ];
+        ifs.prepare_for_chaos_game(false);
+        ifs
+    }
+
+    #[test]
+    fn test_streaming_keeps_points_in_region() {
+        let mut chaos_game = ChaosGame::new();
+        let ifs = gasket();
+
+        let pilot = chaos_game.pilot_camera(&ifs, 10_000, &AutoFrame::default(), 16, 16);
+        assert!(pilot.bin(0.5, 0.5).is_some());
+
+        let camera = Camera::fit(&Range2D::new(0.0..=1.0, 0.0..=1.0), 16, 16);
+        let hist = chaos_game.run_chaos_game_streaming(&ifs, 10_020, &camera);
+        assert_eq!(hist.total_hits(), 10_000);
+
+        // zoomed into the center.
+        let hist = chaos_game.run_chaos_game_streaming(&ifs, 10_020, &camera.with_zoom(1.0));
+        assert!(hist.total_hits() < 10_000);
+    }
+
+    #[test]
+    fn test_final_transform_doesnt_feed_back() {
+        let ifs = AffIfs {
+            name: String::from("gasket x10"),
+            transforms: gasket(),
+            final_transform: Some(IfsTransform::new(AffineMat::from(10.0, 0.0, 0.0, 10.0, 0.0, 0.0), 1.0, 0.0, 0.0)),
+            xaos: None,
+            selector: None
+        };
+
+        // fed back, x10 after x0.5 would blow up.
+        let samples = ChaosGame::new_seeded(7).run_chaos_game(&ifs, 10_000);
+        assert!(samples.iter().all(|coord| (0.0..=10.0).contains(coord)));
+        assert!(samples.iter().any(|coord| *coord > 5.0));
+    }
+
+    #[test]
+    fn test_xaos() {
+        // after whatever comes first, only the top corner's transform - converges to its fixed point (0, 1).
+        let mut ifs = AffIfs {
+            name: String::from("gasket, top only"),
+            transforms: vec![
+                IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 1.0, 0.0, 0.5),
+                IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 1.0, 0.5, 0.5),
+                IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.5), 1.0, 1.0, 0.5),
+            ],
+            final_transform: None,
+            xaos: Some(Xaos::new(vec![vec![0.0, 0.0, 1.0]; 3])),
+            selector: None
+        };
+        ifs.prepare_preset_for_chaos_game();
+
+        let samples = ChaosGame::new_seeded(7).run_chaos_game(&ifs, 1_000);
+        assert!(samples.chunks(2).all(|xy| xy[0].abs() < 1e-3 && (xy[1] - 1.0).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_prebuilt_selector() {
+        let mut ifs = AffIfs { name: String::new(), transforms: gasket(), final_transform: None, xaos: None, selector: None };
+        let built_per_run = ChaosGame::new_seeded(7).run_chaos_game(&ifs, 1_000);
+
+        ifs.prepare_preset_for_chaos_game();
+        assert!(ifs.selector.is_some());
+        assert_eq!(ChaosGame::new_seeded(7).run_chaos_game(&ifs, 1_000), built_per_run);
+    }
+
+    #[test]
+    fn test_selection_frequencies() {
+        // weights by determinant: 0.25, 0.04, 0.0025, 0.5 (abs).
+        let mut ifs = vec![
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.0, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.2, 0.0, 0.0, 0.2, 0.5, 0.0), 0.0, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.05, 0.0, 0.0, 0.05, 0.0, 0.5), 0.0, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.0, 1.0, 0.5, 0.0, 0.0, 0.5), 0.0, 0.0, 0.5),
+        ];
+        ifs.prepare_for_chaos_game(true);
+
+        let expected = [0.25 / 0.7925, 0.04 / 0.7925, 0.0025 / 0.7925, 0.5 / 0.7925];
+        ifs.iter().zip(expected).for_each(|(transform, p)| assert!((transform.p - p).abs() < 1e-6));
+
+        let selector = TransformSelector::new(&ifs);
+        let mut rnd = Modnar::new_rng_seeded(42);
+        let mut counts = [0usize; 4];
+        const SAMPLES: usize = 1_000_000;
+        (0..SAMPLES).for_each(|_| counts[selector.pick(None, rnd.gen_f32())] += 1);
+
+        for (count, p) in counts.iter().zip(expected) {
+            assert!((*count as f32 / SAMPLES as f32 - p).abs() < 0.005);
+        }
+
+        // w. xaos, after 0 - only 1 and 3, by their weights.
+        let ifs = AffIfs { name: String::new(), transforms: ifs, final_transform: None, xaos: Some(Xaos::new(vec![vec![0.0, 1.0, 0.0, 1.0]])), selector: None };
+        let selector = TransformSelector::new(&ifs);
+        let mut counts = [0usize; 4];
+        (0..SAMPLES).for_each(|_| counts[selector.pick(Some(0), rnd.gen_f32())] += 1);
+
+        assert_eq!(counts[0] + counts[2], 0);
+        assert!((counts[1] as f32 / SAMPLES as f32 - 0.04 / 0.54).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_bad_values_are_reseeded() {
+        let mut chaos_game = ChaosGame::new_seeded(7);
+        let samples = chaos_game.run_chaos_game(&gasket(), 10_020);
+        assert_eq!(chaos_game.stats().bad_values, 0);
+        assert_eq!(samples.len(), 2 * 10_000);
+
+        // blows up in ~10 steps, i.e. before the fuse is out - nothing is plotted.
+        let exploding = vec![IfsTransform::new(AffineMat::from(10.0, 0.0, 0.0, 10.0, 1.0, 1.0), 1.0, 0.0, 0.5)];
+        let samples = chaos_game.run_chaos_game(&exploding, 10_000);
+        assert!(samples.is_empty());
+        assert!(chaos_game.stats().bad_values_ratio() > 0.05);
+
+        // 1 in 10 goes through the origin: spherical of (0, 0) is NaN.
+        let mut poisoned = gasket();
+        poisoned.iter_mut().for_each(|transform| transform.p = 0.3);
+        poisoned.push(
+            IfsTransform::new(AffineMat::scale(0.0, 0.0), 0.1, 0.0, 0.5)
+                .with_mutators(vec![MutatorConfig::named(1.0, "spherical", &[]).unwrap()])
+        );
+        let samples = chaos_game.run_chaos_game(&poisoned, 100_000);
+        let ratio = chaos_game.stats().bad_values_ratio();
+        assert!((ratio - 0.1).abs() < 0.01, "{ratio}");
+        assert!(samples.iter().all(|coord| coord.is_finite()));
+        assert!(!samples.is_empty());
+    }
+}
@@ -1,54 +1,54 @@
-use std::cmp::Ordering;
 use serde::Deserialize;
+use crate::chaos_game::TransformSelector;
 use crate::ds::ifs_transform::IfsTransform;
+use crate::ds::xaos::Xaos;
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct AffIfs {
     pub(crate) name: String,
     pub(crate) transforms: Vec<IfsTransform>,
+    /// Applied to each point before it's plotted, doesn't feed back into the iteration.
+    /// Its `p` is ignored.
+    #[serde(default)]
+    pub(crate) final_transform: Option<IfsTransform>,
+    /// Transition weights between the transforms, see `Xaos`.
+    #[serde(default)]
+    pub(crate) xaos: Option<Xaos>,
+    /// Built once by `prepare_preset_for_chaos_game`, and shared by every run (and chunk) of the chaos game.
+    /// Stale, if the transforms or xaos change afterwards - prepare again.
+    #[serde(skip)]
+    pub(crate) selector: Option<TransformSelector>
 }
 
 pub(crate) trait ChaosGamePreprocess {
+    /// Normalizes weights (`p`) of the transforms, `needs_reweigh` - weights by determinant instead.
+    /// Order of the transforms is kept (xaos refers to them by index).
     fn prepare_for_chaos_game(&mut self, needs_reweigh: bool);
+
+    /// Spreads color coordinates of the transforms evenly over [0..1],
+    /// so that each transform gets its own part of the palette.
+    fn spread_colors(&mut self);
 }
 
 impl ChaosGamePreprocess for Vec<IfsTransform> {
     fn prepare_for_chaos_game(&mut self, needs_reweigh: bool) {
-        fn sort_ifs(tfms: &mut Vec<IfsTransform>) {
-            tfms.sort_by(|rhs, lhs| {
-                return if rhs.p > lhs.p {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                };
-            });
-        }
-
-        fn set_cumulative_probs(tfms: &mut Vec<IfsTransform>) {
-            let mut cum_prob = 0.0;
-
-            // - TODO: is there an idiom for this??
-            let last_idx = tfms.len() - 1;
-            for (idx, transform) in tfms.iter_mut().enumerate() {
-                cum_prob += transform.p;
-                transform.p = cum_prob;
-
-                if idx == last_idx { transform.p = 1.0; }
-            }
+        if needs_reweigh {
+            self.iter_mut().for_each(|t| t.p = t.mat.det().abs());
         }
 
-        if !needs_reweigh {
-            sort_ifs(self);
-            set_cumulative_probs(self);
+        let total: f32 = self.iter().map(|t| t.p).sum();
+        if total > 0.0 {
+            self.iter_mut().for_each(|t| t.p /= total);
         } else {
-            let total_det: f32 = self
-                .iter()
-                .map(|e| { e.mat.det().abs() })
-                .sum();
+            let uniform = 1.0 / self.len() as f32;
+            self.iter_mut().for_each(|t| t.p = uniform);
+        }
+    }
 
-            self.iter_mut().for_each(|t| {
-                t.p = t.mat.det().abs()/total_det;
-            });
+    fn spread_colors(&mut self) {
+        let last_idx = (self.len().max(2) - 1) as f32;
+        for (idx, transform) in self.iter_mut().enumerate() {
+            transform.color = idx as f32 / last_idx;
         }
     }
 }
@@ -56,5 +56,6 @@ impl ChaosGamePreprocess for Vec<IfsTransform> {
 impl AffIfs {
     pub(crate) fn prepare_preset_for_chaos_game(&mut self) {
         self.transforms.prepare_for_chaos_game(false);
+        self.selector = Some(TransformSelector::new(self));
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,18 @@
-#[derive(Debug, Clone)]
-pub(crate) struct AffineMat {
-    pub(crate) a: f32,
-    pub(crate) b: f32,
-    pub(crate) c: f32,
-    pub(crate) d: f32,
-    pub(crate) e: f32,
-    pub(crate) f: f32
+use serde::{Deserialize, Serialize};
+
+/// x' = a*x + b*y + e, y' = c*x + d*y + f.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AffineMat {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32
 }
 
 impl AffineMat {
-    pub(crate) fn from(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+    pub fn from(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
         Self {
             a,
             b,
@@ -20,6 +23,30 @@ impl AffineMat {
         }
     }
 
+    pub fn identity() -> Self {
+        Self::from(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub(crate) fn translation(x: f32, y: f32) -> Self {
+        Self::from(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    /// Counterclockwise, radians.
+    pub(crate) fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from(cos, -sin, sin, cos, 0.0, 0.0)
+    }
+
+    /// Negative - reflection.
+    pub(crate) fn scale(x: f32, y: f32) -> Self {
+        Self::from(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// x' = x + k*y.
+    pub(crate) fn shear(k: f32) -> Self {
+        Self::from(1.0, k, 0.0, 1.0, 0.0, 0.0)
+    }
+
     pub(crate) fn det(&self) -> f32 {
         /*
         - TODO: formalize as a type layout
@@ -28,4 +55,162 @@ impl AffineMat {
         */
         self.a*self.d - self.b*self.c
     }
-}
\ No newline at end of file
+
+    pub(crate) fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a*x + self.b*y + self.e, self.c*x + self.d*y + self.f)
+    }
+
+    /// `rhs` first, then `self`.
+    pub(crate) fn compose(&self, rhs: &AffineMat) -> Self {
+        let (e, f) = self.apply(rhs.e, rhs.f);
+
+        Self::from(
+            self.a*rhs.a + self.b*rhs.c,
+            self.a*rhs.b + self.b*rhs.d,
+            self.c*rhs.a + self.d*rhs.c,
+            self.c*rhs.b + self.d*rhs.d,
+            e,
+            f
+        )
+    }
+
+    /// None - singular.
+    pub(crate) fn inverse(&self) -> Option<Self> {
+        let det = self.det();
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Self::from(a, b, c, d, -(a*self.e + b*self.f), -(c*self.e + d*self.f)))
+    }
+
+    /// Largest first. Largest is the most the transform stretches any distance by.
+    pub(crate) fn singular_values(&self) -> (f32, f32) {
+        let sq_sum = self.a*self.a + self.b*self.b + self.c*self.c + self.d*self.d;
+        let det = self.det();
+        let discriminant = (sq_sum*sq_sum - 4.0*det*det).max(0.0).sqrt();
+
+        (((sq_sum + discriminant) / 2.0).sqrt(), ((sq_sum - discriminant) / 2.0).max(0.0).sqrt())
+    }
+
+    /// Strictly shrinks all distances.
+    pub(crate) fn is_contractive(&self) -> bool {
+        self.singular_values().0 < 1.0
+    }
+
+    pub(crate) fn decompose(&self) -> AffineDecomposition {
+        AffineDecomposition::from(self)
+    }
+
+    /// Interpolated component-wise (rotation along the shorter arc), so that `t` = 0.5 of two rotations
+    /// is a rotation, not a squashed matrix.
+    pub(crate) fn lerp(&self, rhs: &AffineMat, t: f32) -> Self {
+        self.decompose().lerp(&rhs.decompose(), t).compose()
+    }
+}
+
+/// `AffineMat` = translation * rotation * shear * scale (scale is applied first).
+/// Reflection shows up as negative `scale_y`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct AffineDecomposition {
+    pub(crate) translation_x: f32,
+    pub(crate) translation_y: f32,
+    /// Radians.
+    pub(crate) rotation: f32,
+    pub(crate) shear: f32,
+    pub(crate) scale_x: f32,
+    pub(crate) scale_y: f32
+}
+
+impl AffineDecomposition {
+    fn from(mat: &AffineMat) -> Self {
+        let scale_x = mat.a.hypot(mat.c);
+        let rotation = if scale_x > 0.0 { mat.c.atan2(mat.a) } else { 0.0 };
+        let (sin, cos) = rotation.sin_cos();
+
+        // rotated back, second column is (shear * scale_y, scale_y).
+        let scale_y = -sin*mat.b + cos*mat.d;
+        let shear = if scale_y.abs() > f32::EPSILON { (cos*mat.b + sin*mat.d) / scale_y } else { 0.0 };
+
+        Self { translation_x: mat.e, translation_y: mat.f, rotation, shear, scale_x, scale_y }
+    }
+
+    pub(crate) fn compose(&self) -> AffineMat {
+        AffineMat::translation(self.translation_x, self.translation_y)
+            .compose(&AffineMat::rotation(self.rotation))
+            .compose(&AffineMat::shear(self.shear))
+            .compose(&AffineMat::scale(self.scale_x, self.scale_y))
+    }
+
+    pub(crate) fn lerp(&self, rhs: &AffineDecomposition, t: f32) -> Self {
+        let lerp = |lhs: f32, rhs: f32| lhs + (rhs - lhs) * t;
+        let turn = std::f32::consts::TAU;
+        let rotation_delta = (rhs.rotation - self.rotation + turn / 2.0).rem_euclid(turn) - turn / 2.0;
+
+        Self {
+            translation_x: lerp(self.translation_x, rhs.translation_x),
+            translation_y: lerp(self.translation_y, rhs.translation_y),
+            rotation: self.rotation + rotation_delta * t,
+            shear: lerp(self.shear, rhs.shear),
+            scale_x: lerp(self.scale_x, rhs.scale_x),
+            scale_y: lerp(self.scale_y, rhs.scale_y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use super::AffineMat;
+
+    fn assert_close(lhs: &AffineMat, rhs: &AffineMat) {
+        let lhs_coefs = [lhs.a, lhs.b, lhs.c, lhs.d, lhs.e, lhs.f];
+        let rhs_coefs = [rhs.a, rhs.b, rhs.c, rhs.d, rhs.e, rhs.f];
+        assert!(lhs_coefs.iter().zip(rhs_coefs).all(|(l, r)| (l - r).abs() < 1e-5), "{lhs:?} vs {rhs:?}");
+    }
+
+    #[test]
+    fn test_compose_and_inverse() {
+        let mat = AffineMat::from(0.5, -0.3, 0.2, 0.7, 0.1, -0.4);
+        assert_close(&mat.compose(&mat.inverse().unwrap()), &AffineMat::identity());
+        assert_close(&mat.inverse().unwrap().compose(&mat), &AffineMat::identity());
+        assert!(AffineMat::scale(1.0, 0.0).inverse().is_none());
+
+        // translate after rotating: (1, 0) -> (0, 1) -> (2, 1).
+        let mat = AffineMat::translation(2.0, 0.0).compose(&AffineMat::rotation(FRAC_PI_2));
+        let (x, y) = mat.apply(1.0, 0.0);
+        assert!((x - 2.0).abs() < 1e-6 && (y - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_decompose() {
+        let mat = AffineMat::from(0.5, -0.3, 0.2, 0.7, 0.1, -0.4);
+        assert_close(&mat.decompose().compose(), &mat);
+
+        let reflection = AffineMat::rotation(0.3).compose(&AffineMat::scale(0.5, -0.25));
+        let parts = reflection.decompose();
+        assert!((parts.rotation - 0.3).abs() < 1e-5 && (parts.scale_y + 0.25).abs() < 1e-5 && parts.shear.abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_singular_values() {
+        let mat = AffineMat::rotation(1.0).compose(&AffineMat::scale(2.0, -0.5)).compose(&AffineMat::rotation(-0.4));
+        let (max, min) = mat.singular_values();
+        assert!((max - 2.0).abs() < 1e-5 && (min - 0.5).abs() < 1e-5);
+        assert!(!mat.is_contractive());
+
+        // shear stretches more than its scale suggests.
+        assert!(!AffineMat::shear(1.0).compose(&AffineMat::scale(0.8, 0.8)).is_contractive());
+        assert!(AffineMat::rotation(2.0).compose(&AffineMat::scale(0.9, 0.5)).is_contractive());
+    }
+
+    #[test]
+    fn test_lerp() {
+        // halfway between 170 and -170 degrees is 180, not 0.
+        let lhs = AffineMat::rotation(170f32.to_radians());
+        let rhs = AffineMat::rotation(-170f32.to_radians());
+        assert_close(&lhs.lerp(&rhs, 0.5), &AffineMat::rotation(std::f32::consts::PI));
+        assert_close(&lhs.lerp(&rhs, 0.0), &lhs);
+    }
+}
@@ -85,6 +85,15 @@ impl Array<2> {
     pub(crate) fn height(&self) -> usize { self.dims[1] }
 
     pub(crate) fn non_zero_count(&self) -> usize { self.array.iter().filter(|val| **val != 0.0).count() }
+
+    /// Element-wise sum, dimensions must match.
+    pub(crate) fn add(&mut self, rhs: &Array2D) {
+        assert_eq!(self.dims, rhs.dims);
+        self.array
+            .iter_mut()
+            .zip(rhs.array.iter())
+            .for_each(|(lhs, rhs)| *lhs += rhs);
+    }
 }
 
 impl Index<Index2D> for Array<2> {
@@ -0,0 +1,151 @@
+use crate::ds::array_2d::{Array2D, Index2D};
+
+/// Per-cell hit counts along with the sum of color coordinates of
+/// all the points, which landed in the cell.
+/// Average color of the cell is `color_sum / hits`.
+///
+/// Both are f64: f32 stops counting at 2^24 hits, which dense cells of long renders exceed.
+/// Filters splat fractional hits into the same cells.
+pub(crate) struct ColorHistogram {
+    width: usize,
+    height: usize,
+    hits: Vec<f64>,
+    color_sum: Vec<f64>,
+    total_hits: u64
+}
+
+impl ColorHistogram {
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            hits: vec![0.0; width * height],
+            color_sum: vec![0.0; width * height],
+            total_hits: 0
+        }
+    }
+
+    pub(crate) fn width(&self) -> usize { self.width }
+    pub(crate) fn height(&self) -> usize { self.height }
+
+    pub(crate) fn total_hits(&self) -> u64 { self.total_hits }
+
+    pub(crate) fn plot(&mut self, index: Index2D, color: f32) {
+        let lin_index = self.lin_index(index);
+        self.hits[lin_index] += 1.0;
+        self.color_sum[lin_index] += color as f64;
+        self.total_hits += 1;
+    }
+
+    /// Same size and total hits, all cells are empty.
+    /// For filters, which redistribute hits of one histogram into another.
+    pub(crate) fn blank_copy(&self) -> Self { self.blank_resized(self.width(), self.height()) }
+
+    /// Same total hits, different size, all cells are empty. I.e. for downsampling.
+    pub(crate) fn blank_resized(&self, width: usize, height: usize) -> Self {
+        let mut res = Self::new(width, height);
+        res.total_hits = self.total_hits;
+        res
+    }
+
+    /// Adds (possibly fractional) hits and their color sum to the cell, total hits are not touched.
+    pub(crate) fn splat(&mut self, index: Index2D, hits: f32, color_sum: f32) {
+        let lin_index = self.lin_index(index);
+        self.hits[lin_index] += hits as f64;
+        self.color_sum[lin_index] += color_sum as f64;
+    }
+
+    /// Adds up hits and color sums of another histogram of the same size.
+    pub(crate) fn merge(&mut self, other: &ColorHistogram) {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        self.hits.iter_mut().zip(&other.hits).for_each(|(lhs, rhs)| *lhs += rhs);
+        self.color_sum.iter_mut().zip(&other.color_sum).for_each(|(lhs, rhs)| *lhs += rhs);
+        self.total_hits += other.total_hits;
+    }
+
+    pub(crate) fn hits(&self, index: Index2D) -> f32 { self.hits[self.lin_index(index)] as f32 }
+    pub(crate) fn color_sum(&self, index: Index2D) -> f32 { self.color_sum[self.lin_index(index)] as f32 }
+
+    /// Average color coordinate of the cell, 0.0 for empty cells.
+    pub(crate) fn color(&self, index: Index2D) -> f32 {
+        let lin_index = self.lin_index(index);
+        let hits = self.hits[lin_index];
+        if hits > 0.0 { (self.color_sum[lin_index] / hits) as f32 } else { 0.0 }
+    }
+
+    /// Hits, normalized by the total number of hits.
+    /// Same thing `DensityEstimator2D::histogram` yields.
+    pub(crate) fn density(&self) -> Array2D {
+        let mut res = Array2D::new(self.width(), self.height());
+        let total = self.total_hits.max(1) as f64;
+
+        for x in 0..self.width() {
+            for y in 0..self.height() {
+                let index = Index2D::from(x, y);
+                res[index] = (self.hits[self.lin_index(index)] / total) as f32;
+            }
+        }
+
+        res
+    }
+
+    pub(crate) fn non_zero_count(&self) -> usize { self.hits.iter().filter(|hits| **hits != 0.0).count() }
+
+    // same layout as `Array2D`.
+    fn lin_index(&self, index: Index2D) -> usize {
+        assert!(index.x() < self.width && index.y() < self.height);
+        index.y() * self.width + index.x()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::array_2d::Index2D;
+    use super::ColorHistogram;
+
+    #[test]
+    fn test_plot_averages_color() {
+        let mut hist = ColorHistogram::new(2, 2);
+        let index = Index2D::from(1, 0);
+
+        hist.plot(index, 0.25);
+        hist.plot(index, 0.75);
+        hist.plot(Index2D::from(0, 1), 1.0);
+
+        assert_eq!(hist.hits(index), 2.0);
+        assert_eq!(hist.color(index), 0.5);
+        assert_eq!(hist.color(Index2D::from(0, 0)), 0.0);
+
+        let density = hist.density();
+        assert!((density[index] - 2.0 / 3.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_merge() {
+        let index = Index2D::from(0, 0);
+        let mut lhs = ColorHistogram::new(1, 1);
+        let mut rhs = ColorHistogram::new(1, 1);
+
+        lhs.plot(index, 0.0);
+        rhs.plot(index, 1.0);
+        lhs.merge(&rhs);
+
+        assert_eq!(lhs.total_hits(), 2);
+        assert_eq!(lhs.hits(index), 2.0);
+        assert_eq!(lhs.color(index), 0.5);
+    }
+
+    #[test]
+    fn test_counts_past_f32() {
+        // f32 stops at 2^24: 2^24 + 1.0 == 2^24.
+        let index = Index2D::from(0, 0);
+        let mut hist = ColorHistogram::new(1, 1);
+        let plots = (1u64 << 24) + 16;
+
+        (0..plots).for_each(|_| hist.plot(index, 0.5));
+
+        assert_eq!(hist.total_hits(), plots);
+        assert_eq!(hist.hits[0], plots as f64);
+        assert_eq!(hist.color(index), 0.5);
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use crate::ds::aff_ifs::AffIfs;
+use crate::ds::ifs_transform::IfsTransform;
+use crate::ds::palette::Palette;
+use crate::ds::symmetry::Symmetry;
+use crate::ds::xaos::Xaos;
+use crate::statistics::camera::Camera;
+use crate::statistics::de_filter::DensityEstimationFilter;
+use crate::statistics::oversampling::Oversampling;
+use crate::tone_mapping::ToneMapping;
+
+/// Everything it takes to render an image, bit-for-bit: same genome - same image
+/// (the chaos game is seeded from `seed`, and is deterministic regardless of the number of threads).
+///
+/// Output size is the size of the camera.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FlameGenome {
+    /// `p` of each transform is its weight (not cumulative, doesn't have to sum up to 1).
+    /// Each transform has its own mutators.
+    pub(crate) transforms: Vec<IfsTransform>,
+    /// Applied to each point before it's plotted, see `AffIfs::final_transform`.
+    #[serde(default)]
+    pub(crate) final_transform: Option<IfsTransform>,
+    #[serde(default)]
+    pub(crate) xaos: Option<Xaos>,
+    /// Symmetry transforms are added on top of `transforms` (after them), see `transforms_with_symmetry`.
+    #[serde(default)]
+    pub(crate) symmetry: Option<Symmetry>,
+    pub(crate) camera: Camera,
+    pub(crate) palette: Palette,
+    pub(crate) tone_mapping: ToneMapping,
+    pub(crate) de_filter: Option<DensityEstimationFilter>,
+    pub(crate) oversampling: Oversampling,
+    pub(crate) iterations: u32,
+    pub(crate) seed: u64
+}
+
+impl FlameGenome {
+    /// Default palette, tone mapping, DE filter and oversampling.
+    pub(crate) fn new(transforms: Vec<IfsTransform>, camera: Camera, iterations: u32, seed: u64) -> Self {
+        assert!(!transforms.is_empty());
+
+        Self {
+            transforms,
+            final_transform: None,
+            xaos: None,
+            symmetry: None,
+            camera,
+            palette: Palette::default(),
+            tone_mapping: ToneMapping::default(),
+            de_filter: Some(DensityEstimationFilter::default()),
+            oversampling: Oversampling::default(),
+            iterations,
+            seed
+        }
+    }
+
+    pub(crate) fn transforms_with_symmetry(&self) -> Vec<IfsTransform> {
+        let mut transforms = self.transforms.clone();
+
+        if let Some(symmetry) = &self.symmetry {
+            transforms.append(&mut symmetry.transforms(&self.transforms));
+        }
+        transforms
+    }
+
+    /// IFS, ready to be fed to the chaos game (weights normalized, transform selector built).
+    pub(crate) fn prepared_ifs(&self) -> AffIfs {
+        let mut ifs = AffIfs {
+            name: String::new(),
+            transforms: self.transforms_with_symmetry(),
+            final_transform: self.final_transform.clone(),
+            xaos: self.xaos.clone(),
+            selector: None
+        };
+
+        ifs.prepare_preset_for_chaos_game();
+        ifs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::ifs_transform::IfsTransform;
+    use crate::ds::symmetry::Symmetry;
+    use crate::ds::xaos::Xaos;
+    use crate::mutators::MutatorConfig;
+    use crate::statistics::camera::Camera;
+    use super::FlameGenome;
+
+    fn genome() -> FlameGenome {
+        FlameGenome::new(
+            vec![
+                IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 2.0, 0.0, 0.5)
+                    .with_mutators(vec![MutatorConfig::named(1.0, "swirl", &[]).unwrap()]),
+                IfsTransform::new(AffineMat::from(0.5, 0.1, 0.0, 0.5, 0.5, 0.0), 1.0, 1.0, 0.25)
+                    .with_mutators(vec![
+                        MutatorConfig::named(0.5, "swirl", &[]).unwrap(),
+                        MutatorConfig::named(0.5, "julian", &[5.0, 0.31]).unwrap()
+                    ])
+                    .with_post(AffineMat::from(1.0, 0.0, 0.0, 1.0, 0.25, 0.0))
+            ],
+            Camera::new(0.5, 0.5, 256.0, 0.1, 512, 256),
+            1_000_000,
+            42
+        )
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut genome = genome();
+        genome.final_transform = Some(IfsTransform::new(AffineMat::from(0.0, -1.0, 1.0, 0.0, 0.0, 0.0), 1.0, 0.5, 0.0));
+        genome.xaos = Some(Xaos::new(vec![vec![0.0, 1.0], vec![1.0, 1.0]]));
+        genome.symmetry = Some(Symmetry::new(5, true));
+
+        let json = serde_json::to_string(&genome).unwrap();
+        let parsed: FlameGenome = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(genome, parsed);
+    }
+
+    #[test]
+    fn test_prepared_transforms() {
+        let prepared = genome().prepared_ifs().transforms;
+
+        // same order.
+        assert!((prepared[0].p - 2.0 / 3.0).abs() < 1e-6);
+        assert!((prepared[1].p - 1.0 / 3.0).abs() < 1e-6);
+
+        let mut genome = genome();
+        genome.symmetry = Some(Symmetry::new(2, false));
+        let prepared = genome.prepared_ifs().transforms;
+
+        // half turn weighs as much as the original.
+        assert_eq!(prepared.len(), 3);
+        assert!((prepared[2].p - 0.5).abs() < 1e-6);
+        assert_eq!(prepared[2].color_speed, 0.0);
+    }
+}
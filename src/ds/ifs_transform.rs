@@ -1,11 +1,91 @@
 use serde::de::Visitor;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::ser::SerializeStruct;
 use crate::ds::affine_mat::AffineMat;
+use crate::ds::point::Point;
+use crate::modnar::Modnar;
+use crate::mutators::{apply_mutator_combination, MutatorConfig};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct IfsTransform {
     pub mat: AffineMat,
     pub p: f32,
+    /// Color coordinate of the transform, in [0..1].
+    pub color: f32,
+    /// How fast the point's color coordinate is pulled towards `color` (0 - not at all, 1 - immediately).
+    pub color_speed: f32,
+    /// Applied after the affine part, when this transform is picked. Empty - none (same as linear).
+    pub mutators: Vec<MutatorConfig>,
+    /// Applied after the mutators (flam3's post-affine). None - identity.
+    pub post: Option<AffineMat>,
+}
+
+impl IfsTransform {
+    pub(crate) const DEFAULT_COLOR_SPEED: f32 = 0.5;
+
+    pub(crate) fn new(mat: AffineMat, p: f32, color: f32, color_speed: f32) -> Self {
+        Self { mat, p, color, color_speed, mutators: Vec::new(), post: None }
+    }
+
+    pub(crate) fn with_mutators(mut self, mutators: Vec<MutatorConfig>) -> Self {
+        self.mutators = mutators;
+        self
+    }
+
+    pub(crate) fn with_post(mut self, post: AffineMat) -> Self {
+        self.post = Some(post);
+        self
+    }
+
+    /// pre-affine -> mutators -> post-affine.
+    pub(crate) fn apply(&self, point: &Point, rnd: &mut Modnar) -> Point {
+        let mut res = point.clone();
+        res.transform(&self.mat);
+
+        if !self.mutators.is_empty() {
+            res = apply_mutator_combination(&self.mutators, &res, &self.mat, rnd);
+        }
+
+        if let Some(post) = &self.post {
+            res.transform(post);
+        }
+
+        res
+    }
+
+    /// FLAM3 color blending: c' = c*(1 - speed) + speed*color.
+    pub(crate) fn blend_color(&self, color: f32) -> f32 {
+        color * (1.0 - self.color_speed) + self.color_speed * self.color
+    }
+}
+
+// same flat layout as in the presets.
+impl Serialize for IfsTransform {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("IfsTransform", 11)?;
+        state.serialize_field("a", &self.mat.a)?;
+        state.serialize_field("b", &self.mat.b)?;
+        state.serialize_field("c", &self.mat.c)?;
+        state.serialize_field("d", &self.mat.d)?;
+        state.serialize_field("e", &self.mat.e)?;
+        state.serialize_field("f", &self.mat.f)?;
+        state.serialize_field("p", &self.p)?;
+        state.serialize_field("color", &self.color)?;
+        state.serialize_field("color_speed", &self.color_speed)?;
+        if self.mutators.is_empty() {
+            state.skip_field("mutators")?;
+        } else {
+            state.serialize_field("mutators", &self.mutators)?;
+        }
+        match &self.post {
+            Some(post) => state.serialize_field("post", post)?,
+            None => state.skip_field("post")?
+        }
+        state.end()
+    }
 }
 
 impl<'de> Deserialize<'de> for IfsTransform {
@@ -16,7 +96,7 @@ impl<'de> Deserialize<'de> for IfsTransform {
         #[rustfmt::skip]
         #[allow(non_camel_case_types)]
         #[derive(Deserialize)]
-        enum Fields { a, b, c, d, e, f, p }
+        enum Fields { a, b, c, d, e, f, p, color, color_speed, mutators, post }
 
         struct AffineTransformVisitor;
         impl<'de> Visitor<'de> for AffineTransformVisitor {
@@ -37,6 +117,10 @@ impl<'de> Deserialize<'de> for IfsTransform {
                 let mut e: Option<f32> = None;
                 let mut f: Option<f32> = None;
                 let mut p: Option<f32> = None;
+                let mut color: Option<f32> = None;
+                let mut color_speed: Option<f32> = None;
+                let mut mutators: Option<Vec<MutatorConfig>> = None;
+                let mut post: Option<AffineMat> = None;
 
                 // jfc. Visitor sees map, and expects its keys to be of the type
                 // inferred from match. To parse this type it invokes Visitor for FieldsType (which is auto derived).
@@ -49,6 +133,10 @@ impl<'de> Deserialize<'de> for IfsTransform {
                         Fields::e => e = map.next_value()?,
                         Fields::f => f = map.next_value()?,
                         Fields::p => p = map.next_value()?,
+                        Fields::color => color = map.next_value()?,
+                        Fields::color_speed => color_speed = map.next_value()?,
+                        Fields::mutators => mutators = map.next_value()?,
+                        Fields::post => post = map.next_value()?,
                     }
                 }
 
@@ -60,13 +148,51 @@ impl<'de> Deserialize<'de> for IfsTransform {
                 let f = f.ok_or_else(|| serde::de::Error::missing_field("f"))?;
                 let p = p.ok_or_else(|| serde::de::Error::missing_field("p"))?;
 
+                // presets, which predate coloring, don't have these.
+                let color = color.unwrap_or(0.0);
+                let color_speed = color_speed.unwrap_or(IfsTransform::DEFAULT_COLOR_SPEED);
+                let mutators = mutators.unwrap_or_default();
+
                 return Ok(IfsTransform {
                     mat: AffineMat::from(a, b, c, d, e, f),
                     p,
+                    color,
+                    color_speed,
+                    mutators,
+                    post
                 });
             }
         }
 
         return deserializer.deserialize_map(AffineTransformVisitor);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use super::IfsTransform;
+
+    #[test]
+    fn test_deserialize_without_color() {
+        let json = r#"{ "a": 0.5, "b": 0, "c": 0, "d": 0, "e": 0.5, "f": 0, "p": 0.33 }"#;
+        let transform: IfsTransform = serde_json::from_str(json).expect("Can't parse transform!");
+
+        assert_eq!(transform.color, 0.0);
+        assert_eq!(transform.color_speed, IfsTransform::DEFAULT_COLOR_SPEED);
+    }
+
+    #[test]
+    fn test_blend_color() {
+        let mat = AffineMat::from(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+        let still = IfsTransform::new(mat.clone(), 1.0, 1.0, 0.0);
+        assert_eq!(still.blend_color(0.25), 0.25);
+
+        let instant = IfsTransform::new(mat.clone(), 1.0, 1.0, 1.0);
+        assert_eq!(instant.blend_color(0.25), 1.0);
+
+        let half = IfsTransform::new(mat, 1.0, 1.0, 0.5);
+        assert_eq!(half.blend_color(0.0), 0.5);
+    }
+}
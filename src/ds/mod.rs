@@ -2,4 +2,10 @@ pub(crate) mod array_2d;
 pub(crate) mod affine_mat;
 pub(crate) mod ifs_transform;
 pub(crate) mod aff_ifs;
-pub(crate) mod point;
\ No newline at end of file
+pub(crate) mod point;
+pub(crate) mod color_histogram;
+pub(crate) mod palette;
+pub(crate) mod flame_genome;
+pub(crate) mod xaos;
+pub(crate) mod symmetry;
+
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+/// 256 entry RGB gradient, channels are in [0..1].
+/// Color coordinate [0..1] of the chaos game point is mapped onto it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Palette {
+    pub(crate) name: String,
+    colors: Vec<[f32; 3]>
+}
+
+impl Palette {
+    pub(crate) const SIZE: usize = 256;
+
+    /// `colors` are resampled to `SIZE` entries, if there are more or less of them.
+    pub(crate) fn new(name: &str, colors: Vec<[f32; 3]>) -> Self {
+        assert!(!colors.is_empty());
+
+        if colors.len() != Self::SIZE {
+            let stops: Vec<(f32, [f32; 3])> = colors
+                .iter()
+                .enumerate()
+                .map(|(idx, rgb)| (idx as f32 / (colors.len().max(2) - 1) as f32, *rgb))
+                .collect();
+            return Self::from_stops(name, &stops);
+        }
+
+        Self { name: name.to_string(), colors }
+    }
+
+    /// Builds a gradient, linearly interpolating between the stops.
+    /// Stops are (position in [0..1], rgb), not necessarily sorted.
+    pub(crate) fn from_stops(name: &str, stops: &[(f32, [f32; 3])]) -> Self {
+        assert!(!stops.is_empty());
+
+        let mut stops = stops.to_vec();
+        stops.sort_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0));
+
+        let colors = (0..Self::SIZE).map(|idx| {
+            let pos = idx as f32 / (Self::SIZE - 1) as f32;
+            let after = stops.iter().position(|stop| stop.0 >= pos);
+
+            match after {
+                None => stops[stops.len() - 1].1,
+                Some(0) => stops[0].1,
+                Some(after) => {
+                    let (lo_pos, lo) = stops[after - 1];
+                    let (hi_pos, hi) = stops[after];
+                    let t = (pos - lo_pos) / (hi_pos - lo_pos).max(f32::EPSILON);
+                    Self::lerp(&lo, &hi, t)
+                }
+            }
+        }).collect();
+
+        Self { name: name.to_string(), colors }
+    }
+
+    pub(crate) fn colors(&self) -> &[[f32; 3]] { &self.colors }
+
+    /// Color for the color coordinate, linearly interpolated between adjacent entries.
+    pub(crate) fn lookup(&self, color: f32) -> [f32; 3] {
+        let pos = color.clamp(0.0, 1.0) * (Self::SIZE - 1) as f32;
+        let lo = pos.floor() as usize;
+        let hi = (lo + 1).min(Self::SIZE - 1);
+
+        Self::lerp(&self.colors[lo], &self.colors[hi], pos - lo as f32)
+    }
+
+    pub(crate) fn lookup_u8(&self, color: f32) -> [u8; 3] {
+        self.lookup(color).map(|channel| (255.0 * channel).round() as u8)
+    }
+
+    /// Blends two palettes entry by entry, `t` = 0 yields `self`, 1 - `other`.
+    pub(crate) fn interpolate(&self, other: &Palette, t: f32) -> Palette {
+        let colors = self.colors
+            .iter()
+            .zip(other.colors.iter())
+            .map(|(lhs, rhs)| Self::lerp(lhs, rhs, t))
+            .collect();
+
+        Self { name: format!("{}-{}", self.name, other.name), colors }
+    }
+
+    fn lerp(lhs: &[f32; 3], rhs: &[f32; 3], t: f32) -> [f32; 3] {
+        [
+            lhs[0] + (rhs[0] - lhs[0]) * t,
+            lhs[1] + (rhs[1] - lhs[1]) * t,
+            lhs[2] + (rhs[2] - lhs[2]) * t
+        ]
+    }
+}
+
+/// Built-in palettes.
+impl Palette {
+    pub(crate) const BUILTIN_NAMES: [&'static str; 6] = ["grayscale", "fire", "ocean", "rainbow", "sunset", "emerald"];
+
+    pub(crate) fn builtin(name: &str) -> Option<Palette> {
+        let stops: &[(f32, [f32; 3])] = match name {
+            "grayscale" => &[(0.0, [0.0, 0.0, 0.0]), (1.0, [1.0, 1.0, 1.0])],
+            "fire" => &[
+                (0.0, [0.2, 0.0, 0.0]),
+                (0.35, [0.9, 0.1, 0.0]),
+                (0.7, [1.0, 0.65, 0.0]),
+                (1.0, [1.0, 1.0, 0.8])
+            ],
+            "ocean" => &[
+                (0.0, [0.0, 0.05, 0.2]),
+                (0.5, [0.0, 0.45, 0.7]),
+                (1.0, [0.7, 1.0, 1.0])
+            ],
+            "rainbow" => &[
+                (0.0, [1.0, 0.0, 0.0]),
+                (0.2, [1.0, 1.0, 0.0]),
+                (0.4, [0.0, 1.0, 0.0]),
+                (0.6, [0.0, 1.0, 1.0]),
+                (0.8, [0.0, 0.0, 1.0]),
+                (1.0, [1.0, 0.0, 1.0])
+            ],
+            "sunset" => &[
+                (0.0, [0.15, 0.0, 0.3]),
+                (0.4, [0.8, 0.2, 0.4]),
+                (0.75, [1.0, 0.55, 0.2]),
+                (1.0, [1.0, 0.9, 0.5])
+            ],
+            "emerald" => &[
+                (0.0, [0.0, 0.15, 0.05]),
+                (0.6, [0.1, 0.7, 0.35]),
+                (1.0, [0.85, 1.0, 0.85])
+            ],
+            _ => return None
+        };
+
+        Some(Self::from_stops(name, stops))
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::builtin("fire").unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Palette;
+
+    #[test]
+    fn test_from_stops_endpoints() {
+        let palette = Palette::builtin("grayscale").unwrap();
+
+        assert_eq!(palette.colors().len(), Palette::SIZE);
+        assert_eq!(palette.lookup(0.0), [0.0, 0.0, 0.0]);
+        assert_eq!(palette.lookup(1.0), [1.0, 1.0, 1.0]);
+        assert!((palette.lookup(0.5)[0] - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_resample_and_interpolate() {
+        let black_white = Palette::new("bw", vec![[0.0; 3], [1.0; 3]]);
+        let white = Palette::new("white", vec![[1.0; 3]; Palette::SIZE]);
+
+        assert_eq!(black_white.colors().len(), Palette::SIZE);
+
+        let blend = black_white.interpolate(&white, 0.5);
+        assert_eq!(blend.lookup(0.0), [0.5, 0.5, 0.5]);
+        assert_eq!(blend.lookup(1.0), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_builtins_exist() {
+        Palette::BUILTIN_NAMES.iter().for_each(|name| { Palette::builtin(name).expect("Missing builtin!"); });
+        assert!(Palette::builtin("nope").is_none());
+    }
+}
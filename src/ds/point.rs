@@ -1,20 +1,21 @@
 use crate::ds::affine_mat::AffineMat;
 
 #[derive(Clone)]
-pub(crate) struct Point {
-    pub(crate) x: f32,
-    pub(crate) y: f32
+pub struct Point {
+    pub x: f32,
+    pub y: f32
 }
 
 impl Point {
-    pub(crate) fn new(x: f32, y: f32) ->  Self {
+    pub fn new(x: f32, y: f32) ->  Self {
         Self { x, y }
     }
-    pub(crate) fn zero() -> Self { Self { x: 0.0, y: 0.0 } }
+    pub fn zero() -> Self { Self { x: 0.0, y: 0.0 } }
 
     pub(crate) fn transform(&mut self, mat: &AffineMat) {
-        self.x = self.x*mat.a + self.y*mat.b + mat.e;
+        let x = self.x*mat.a + self.y*mat.b + mat.e;
         self.y = self.x*mat.c + self.y*mat.d + mat.f;
+        self.x = x;
     }
 
     pub(crate) fn dst_fast(&self, rhs: &Point) -> f32 {
@@ -30,4 +31,35 @@ impl Point {
     pub(crate) fn len(&self) -> f32 {
         self.dot(self)
     }
-}
\ No newline at end of file
+}
+
+/// A point, iterated by the chaos game, along with its running color coordinate.
+#[derive(Clone)]
+pub(crate) struct ColoredPoint {
+    pub(crate) point: Point,
+    pub(crate) color: f32
+}
+
+impl ColoredPoint {
+    pub(crate) fn new(point: Point, color: f32) -> Self {
+        Self { point, color }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use super::Point;
+
+    #[test]
+    fn test_transform() {
+        // quarter turn, y' must be computed from the original x.
+        let mut p = Point::new(1.0, 0.0);
+        p.transform(&AffineMat::from(0.0, -1.0, 1.0, 0.0, 0.5, 0.0));
+        assert_eq!((p.x, p.y), (0.5, 1.0));
+
+        let mut p = Point::new(2.0, 3.0);
+        p.transform(&AffineMat::from(1.0, 2.0, 3.0, 4.0, 5.0, 6.0));
+        assert_eq!((p.x, p.y), (13.0, 24.0));
+    }
+}
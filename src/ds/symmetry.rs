@@ -0,0 +1,98 @@
+use std::f32::consts::PI;
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use crate::ds::affine_mat::AffineMat;
+use crate::ds::ifs_transform::IfsTransform;
+
+/// Rotational symmetry of order `order` (rotations by 2*pi*k/order),
+/// w. `mirror` - dihedral (also reflections, the first one is x -> -x, as in flam3).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Symmetry {
+    pub(crate) order: u8,
+    pub(crate) mirror: bool
+}
+
+impl Symmetry {
+    pub(crate) fn new(order: u8, mirror: bool) -> Self {
+        assert!(order >= 1);
+        Self { order, mirror }
+    }
+
+    /// Elements of the symmetry group, except the identity.
+    pub(crate) fn elements(&self) -> Vec<AffineMat> {
+        let angles = (0..self.order).map(|k| 2.0 * PI * k as f32 / self.order as f32);
+
+        let rotations = angles
+            .clone()
+            .skip(1)
+            .map(AffineMat::rotation);
+
+        // rotation after x -> -x.
+        let reflections = angles
+            .filter(|_| self.mirror)
+            .map(|angle| AffineMat::rotation(angle).compose(&AffineMat::scale(-1.0, 1.0)));
+
+        rotations.chain(reflections).collect()
+    }
+
+    /// Transforms to add to `ifs`, one per element.
+    /// Color speed is 0, so they don't touch the colors.
+    /// Together they weigh as much as the whole `ifs`, evenly, so the copies are as dense as the original.
+    pub(crate) fn transforms(&self, ifs: &[IfsTransform]) -> Vec<IfsTransform> {
+        let elements = self.elements();
+        let total: f32 = ifs.iter().map(|transform| transform.p).sum();
+        let p = total / elements.len().max(1) as f32;
+
+        elements
+            .into_iter()
+            .map(|mat| IfsTransform::new(mat, p, 0.0, 0.0))
+            .collect()
+    }
+}
+
+// c4 - rotational, d4 - dihedral.
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", if self.mirror { "d" } else { "c" }, self.order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::ifs_transform::IfsTransform;
+    use crate::ds::point::Point;
+    use super::Symmetry;
+
+    #[test]
+    fn test_elements() {
+        assert!(Symmetry::new(1, false).elements().is_empty());
+        assert_eq!(Symmetry::new(1, true).elements(), vec![AffineMat::from(-1.0, 0.0, 0.0, 1.0, 0.0, 0.0)]);
+
+        let dihedral = Symmetry::new(4, true);
+        let elements = dihedral.elements();
+        assert_eq!(elements.len(), 7);
+        assert_eq!(dihedral.to_string(), "d4");
+
+        // quarter turn: (1, 0) -> (0, 1).
+        let mut point = Point::new(1.0, 0.0);
+        point.transform(&elements[0]);
+        assert!(point.x.abs() < 1e-6 && (point.y - 1.0).abs() < 1e-6);
+
+        // rotations keep orientation, reflections flip it.
+        elements[..3].iter().for_each(|mat| assert!((mat.det() - 1.0).abs() < 1e-6));
+        elements[3..].iter().for_each(|mat| assert!((mat.det() + 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_transforms() {
+        let ifs = vec![
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.25, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 0.75, 1.0, 0.5),
+        ];
+        let transforms = Symmetry::new(3, false).transforms(&ifs);
+
+        assert_eq!(transforms.len(), 2);
+        assert!(transforms.iter().all(|transform| transform.color_speed == 0.0 && transform.p == 0.5));
+    }
+}
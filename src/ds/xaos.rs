@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// Xaos (flam3's "chaos"): transform choice depends on the previously picked transform.
+/// Probability of picking transform j after transform i is proportional to `weight(j) * weights[i][j]`,
+/// so all ones is the plain IFS, and 0 forbids the transition (i.e. Markov / graph-directed IFS).
+///
+/// Transforms are referred to by index, so the order of the transforms must not change.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Xaos {
+    weights: Vec<Vec<f32>>
+}
+
+impl Xaos {
+    /// Missing entries are 1.
+    pub(crate) fn new(weights: Vec<Vec<f32>>) -> Self {
+        Self { weights }
+    }
+
+    pub(crate) fn weights(&self) -> &Vec<Vec<f32>> { &self.weights }
+
+    /// Whether it's `transforms` x `transforms`. Genomes and presets are checked on load.
+    pub(crate) fn fits(&self, transforms: usize) -> bool {
+        self.weights.len() == transforms && self.weights.iter().all(|row| row.len() == transforms)
+    }
+
+    /// Multiplier of transform `to`'s weight, after `from`.
+    pub(crate) fn weight(&self, from: usize, to: usize) -> f32 {
+        self.weights
+            .get(from)
+            .and_then(|row| row.get(to))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Weights of the transforms after `from`, `transform_weights` - their own weights.
+    /// A transform w/o any allowed transition out of it is followed as if there were no xaos.
+    pub(crate) fn row(&self, from: usize, transform_weights: &[f32]) -> Vec<f32> {
+        let row: Vec<f32> = transform_weights
+            .iter()
+            .enumerate()
+            .map(|(to, weight)| weight * self.weight(from, to).max(0.0))
+            .collect();
+
+        if row.iter().sum::<f32>() > 0.0 {
+            row
+        } else {
+            transform_weights.to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Xaos;
+
+    #[test]
+    fn test_row() {
+        let xaos = Xaos::new(vec![
+            vec![0.0, 2.0, 0.0],
+            vec![0.0, 0.0, 0.0]
+        ]);
+        let weights = [1.0, 0.5, 3.0];
+
+        assert_eq!(xaos.row(0, &weights), vec![0.0, 1.0, 0.0]);
+        // forbids everything - plain weights.
+        assert_eq!(xaos.row(1, &weights), weights.to_vec());
+        // missing - all ones.
+        assert_eq!(xaos.row(2, &weights), weights.to_vec());
+    }
+
+    #[test]
+    fn test_fits() {
+        assert!(Xaos::new(vec![vec![0.0, 1.0], vec![1.0, 0.0]]).fits(2));
+        assert!(!Xaos::new(vec![vec![0.0, 1.0], vec![1.0, 0.0]]).fits(3));
+        assert!(!Xaos::new(vec![vec![0.0, 1.0], vec![1.0]]).fits(2));
+        assert!(!Xaos::new(vec![vec![0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0]]).fits(2));
+    }
+}
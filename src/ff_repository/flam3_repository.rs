@@ -0,0 +1,524 @@
+use std::fmt::Display;
+use crate::ds::affine_mat::AffineMat;
+use crate::ds::flame_genome::FlameGenome;
+use crate::ds::ifs_transform::IfsTransform;
+use crate::ds::palette::Palette;
+use crate::ds::xaos::Xaos;
+use crate::ff_repository::json_helper::JsonHelper;
+use crate::ff_repository::palette_repository::PaletteRepository;
+use crate::ff_repository::repository_error::RepositoryError;
+use crate::ff_repository::xml_helper::{XmlHelper, XmlNode};
+use crate::mutators::flam3::{from_flam3, is_flam3_param, to_flam3};
+use crate::statistics::camera::Camera;
+use crate::statistics::de_filter::DensityEstimationFilter;
+use crate::statistics::oversampling::{Oversampling, ReconstructionFilter};
+
+/// A flame, imported from a .flam3 / .flame file, along with what got lost on the way.
+pub(crate) struct Flam3Flame {
+    pub(crate) name: String,
+    pub(crate) genome: FlameGenome,
+    pub(crate) report: ImportReport
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ImportReport {
+    /// Variations, which have no `Mutator` counterpart (dropped).
+    pub(crate) unsupported_variations: Vec<String>,
+    /// Xform attributes and elements, which are not supported (ignored).
+    pub(crate) ignored: Vec<String>,
+    /// Anything else, that makes the import not exact.
+    pub(crate) warnings: Vec<String>
+}
+
+impl ImportReport {
+    pub(crate) fn is_exact(&self) -> bool {
+        self.unsupported_variations.is_empty() && self.ignored.is_empty() && self.warnings.is_empty()
+    }
+
+    fn push_unique(list: &mut Vec<String>, entry: &str) {
+        if !list.iter().any(|existing| existing == entry) {
+            list.push(entry.to_string());
+        }
+    }
+}
+
+impl Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_exact() {
+            return write!(f, "exact");
+        }
+
+        if !self.unsupported_variations.is_empty() {
+            writeln!(f, "unsupported variations: {}", self.unsupported_variations.join(", "))?;
+        }
+        if !self.ignored.is_empty() {
+            writeln!(f, "ignored: {}", self.ignored.join(", "))?;
+        }
+        self.warnings.iter().try_for_each(|warning| writeln!(f, "{warning}"))
+    }
+}
+
+/// flam3 XML genomes (as written by flam3, Apophysis, Chaotica etc.):
+/// `<flames><flame ...><xform .../>...<palette .../></flame>...</flames>`, or a single `<flame>`.
+///
+/// Mapping:
+/// 1. `coefs="xx xy yx yy ox oy"` - x' = xx*x + yx*y + ox, y' = xy*x + yy*y + oy, i.e. `AffineMat` (xx, yx, xy, yy, ox, oy).
+///    `post` - the same, `finalxform` - `FlameGenome::final_transform`, xform's `chaos` - its row of `Xaos`.
+/// 2. xform `weight` - `p`, `color` and `color_speed` (or legacy `symmetry`) as is.
+/// 3. variation attributes - `MutatorConfig`, see `mutators::flam3`.
+/// 4. `size`, `center`, `scale`, `zoom`, `rotate` (degrees) - `Camera`; `quality` (samples per pixel) - iterations.
+/// 5. tone mapping, `estimator_*` - DE filter, `oversample`/`supersample`, `filter`, `filter_shape` - oversampling.
+pub(crate) struct Flam3Repository {
+    flames: Vec<Flam3Flame>
+}
+
+impl AsRef<Vec<Flam3Flame>> for Flam3Repository {
+    fn as_ref(&self) -> &Vec<Flam3Flame> {
+        &self.flames
+    }
+}
+
+impl Flam3Repository {
+    // xform attributes, which aren't variations.
+    const XFORM_ATTRIBUTES: [&'static str; 8] = ["weight", "color", "color_speed", "symmetry", "coefs", "post", "chaos", "name"];
+
+    pub(crate) fn load(db_path: &str) -> Result<Self, RepositoryError> {
+        let xml = JsonHelper::read_db(db_path)?;
+        Self::parse(&xml).map(|flames| Self { flames })
+    }
+
+    pub(crate) fn find_flame_by(&self, name: &str) -> Option<&Flam3Flame> {
+        self.flames.iter().find(|flame| flame.name == name)
+    }
+
+    pub(crate) fn save(flames: &[(&str, &FlameGenome)], db_path: &str) -> Result<(), RepositoryError> {
+        std::fs::write(db_path, Self::write(flames)).map_err(|_| RepositoryError::FileNotWritten)
+    }
+
+    pub(crate) fn parse(xml: &str) -> Result<Vec<Flam3Flame>, RepositoryError> {
+        let root = XmlHelper::parse(xml)?;
+        let mut flame_nodes = Vec::new();
+        root.find_all("flame", &mut flame_nodes);
+
+        if flame_nodes.is_empty() {
+            return Err(RepositoryError::XMLDecoding);
+        }
+
+        flame_nodes.into_iter().map(Self::parse_flame).collect()
+    }
+
+    pub(crate) fn write(flames: &[(&str, &FlameGenome)]) -> String {
+        let mut root = XmlNode::new("flames");
+        root.children = flames
+            .iter()
+            .map(|(name, genome)| Self::write_flame(name, genome))
+            .collect();
+
+        XmlHelper::write(&root)
+    }
+
+    fn parse_flame(node: &XmlNode) -> Result<Flam3Flame, RepositoryError> {
+        let mut report = ImportReport::default();
+        let name = node.attr("name").unwrap_or("unnamed").to_string();
+
+        let transforms = node
+            .children_named("xform")
+            .map(|xform| Self::parse_xform(xform, &mut report))
+            .collect::<Result<Vec<IfsTransform>, RepositoryError>>()?;
+
+        if transforms.is_empty() {
+            return Err(RepositoryError::XMLDecoding);
+        }
+
+        // missing rows and trailing entries are ones.
+        let xaos = if node.children_named("xform").any(|xform| xform.attr("chaos").is_some()) {
+            let mut weights = node
+                .children_named("xform")
+                .map(|xform| xform.attr("chaos").map(Self::parse_floats).unwrap_or(Ok(Vec::new())))
+                .collect::<Result<Vec<Vec<f32>>, RepositoryError>>()?;
+            if weights.iter().any(|row| row.len() > transforms.len()) {
+                return Err(RepositoryError::XaosSize { transforms: transforms.len() });
+            }
+            weights.iter_mut().for_each(|row| row.resize(transforms.len(), 1.0));
+            Some(Xaos::new(weights))
+        } else {
+            None
+        };
+
+        let final_transform = node
+            .children_named("finalxform")
+            .next()
+            .map(|xform| Self::parse_xform(xform, &mut report))
+            .transpose()?;
+
+        let camera = Self::parse_camera(node)?;
+        let quality = node.attr_f32("quality").unwrap_or(1.0);
+        let iterations = (quality as f64 * (camera.width * camera.height) as f64).min(u32::MAX as f64) as u32;
+        let seed = node.attr("seed").and_then(|seed| seed.parse::<u64>().ok()).unwrap_or(0);
+
+        let mut genome = FlameGenome::new(transforms, camera, iterations, seed);
+        genome.final_transform = final_transform;
+        genome.xaos = xaos;
+
+        genome.palette = match PaletteRepository::parse_flame_palette(node) {
+            Ok(palette) => palette,
+            Err(_) => {
+                report.warnings.push(String::from("no palette, default one is used"));
+                Palette::default()
+            }
+        };
+
+        let tone_mapping = &mut genome.tone_mapping;
+        tone_mapping.brightness = node.attr_f32("brightness").unwrap_or(tone_mapping.brightness);
+        tone_mapping.contrast = node.attr_f32("contrast").unwrap_or(tone_mapping.contrast);
+        tone_mapping.gamma = node.attr_f32("gamma").unwrap_or(tone_mapping.gamma);
+        tone_mapping.gamma_threshold = node.attr_f32("gamma_threshold").unwrap_or(tone_mapping.gamma_threshold);
+        tone_mapping.vibrancy = node.attr_f32("vibrancy").unwrap_or(tone_mapping.vibrancy);
+        tone_mapping.highlight_power = node.attr_f32("highlight_power").unwrap_or(tone_mapping.highlight_power);
+
+        let de_default = DensityEstimationFilter::default();
+        let max_radius = node.attr_f32("estimator_radius").unwrap_or(de_default.max_radius);
+        genome.de_filter = if max_radius > 0.0 {
+            Some(DensityEstimationFilter {
+                min_radius: node.attr_f32("estimator_minimum").unwrap_or(de_default.min_radius).min(max_radius),
+                max_radius,
+                curve: node.attr_f32("estimator_curve").unwrap_or(de_default.curve)
+            })
+        } else {
+            None
+        };
+
+        genome.oversampling = Self::parse_oversampling(node, &mut report);
+
+        Ok(Flam3Flame { name, genome, report })
+    }
+
+    fn parse_xform(xform: &XmlNode, report: &mut ImportReport) -> Result<IfsTransform, RepositoryError> {
+        let mat = Self::parse_coefs(xform.attr("coefs").ok_or(RepositoryError::XMLDecoding)?)?;
+
+        let color_speed = xform
+            .attr_f32("color_speed")
+            .or_else(|| xform.attr_f32("symmetry").map(|symmetry| (1.0 - symmetry) / 2.0))
+            .unwrap_or(IfsTransform::DEFAULT_COLOR_SPEED);
+
+        let mut transform = IfsTransform::new(
+            mat,
+            xform.attr_f32("weight").unwrap_or(1.0),
+            xform.attr_f32("color").unwrap_or(0.0),
+            color_speed
+        );
+
+        // identity is the default.
+        if let Some(post) = xform.attr("post") {
+            let post = Self::parse_coefs(post)?;
+            if post != AffineMat::from(1.0, 0.0, 0.0, 1.0, 0.0, 0.0) {
+                transform = transform.with_post(post);
+            }
+        }
+
+        let mut mutators = Vec::new();
+        for (key, value) in xform.attributes.iter() {
+            if Self::XFORM_ATTRIBUTES.contains(&key.as_str()) || is_flam3_param(key) {
+                continue;
+            }
+
+            let Ok(weight) = value.trim().parse::<f32>() else {
+                ImportReport::push_unique(&mut report.ignored, key);
+                continue;
+            };
+
+            match from_flam3(key, weight, |param| xform.attr_f32(param)) {
+                Some(mutator) => mutators.push(mutator),
+                // parameters of unsupported variations are skipped along with them.
+                None if xform.attributes.iter().any(|(other, _)| key.starts_with(&format!("{other}_"))) => {}
+                None if Self::is_structural(key) => ImportReport::push_unique(&mut report.ignored, key),
+                None => ImportReport::push_unique(&mut report.unsupported_variations, key)
+            }
+        }
+
+        // no mutators is identity, same as a lone linear.
+        if let [single] = mutators.as_slice() {
+            if single.mutator.name() == "linear" && single.weight == 1.0 {
+                mutators.clear();
+            }
+        }
+
+        Ok(transform.with_mutators(mutators))
+    }
+
+    fn parse_coefs(value: &str) -> Result<AffineMat, RepositoryError> {
+        let coefs = Self::parse_floats(value)?;
+        let [xx, xy, yx, yy, ox, oy] = coefs[..] else { return Err(RepositoryError::XMLDecoding) };
+
+        Ok(AffineMat::from(xx, yx, xy, yy, ox, oy))
+    }
+
+    // numeric xform attributes, which are not variations.
+    fn is_structural(attr: &str) -> bool {
+        ["opacity", "animate", "var_color", "motion_frequency"].contains(&attr)
+    }
+
+    fn parse_camera(node: &XmlNode) -> Result<Camera, RepositoryError> {
+        let size = match node.attr("size") {
+            Some(size) => Self::parse_floats(size)?,
+            None => vec![100.0, 100.0]
+        };
+        let center = match node.attr("center") {
+            Some(center) => Self::parse_floats(center)?,
+            None => vec![0.0, 0.0]
+        };
+
+        let (&[width, height], &[center_x, center_y]) = (size.as_slice(), center.as_slice()) else {
+            return Err(RepositoryError::XMLDecoding)
+        };
+
+        if width < 1.0 || height < 1.0 {
+            return Err(RepositoryError::XMLDecoding);
+        }
+
+        Ok(Camera::new(
+            center_x,
+            center_y,
+            node.attr_f32("scale").unwrap_or(50.0),
+            node.attr_f32("rotate").unwrap_or(0.0).to_radians(),
+            width as usize,
+            height as usize
+        ).with_zoom(node.attr_f32("zoom").unwrap_or(0.0)))
+    }
+
+    fn parse_oversampling(node: &XmlNode, report: &mut ImportReport) -> Oversampling {
+        let factor = node
+            .attr_f32("supersample")
+            .or_else(|| node.attr_f32("oversample"))
+            .unwrap_or(1.0)
+            .round()
+            .max(1.0) as usize;
+
+        let filter = match node.attr("filter_shape").unwrap_or("gaussian") {
+            "gaussian" => ReconstructionFilter::Gaussian,
+            "box" => ReconstructionFilter::Box,
+            "mitchell" => ReconstructionFilter::Mitchell,
+            "lanczos3" | "lanczos2" => ReconstructionFilter::Lanczos,
+            other => {
+                report.warnings.push(format!("filter shape {other} is not supported, gaussian is used"));
+                ReconstructionFilter::Gaussian
+            }
+        };
+
+        let radius = node.attr_f32("filter").filter(|radius| *radius > 0.0).unwrap_or(1.0);
+
+        Oversampling::new(factor, filter, radius)
+    }
+
+    fn write_flame(name: &str, genome: &FlameGenome) -> XmlNode {
+        let mut node = XmlNode::new("flame");
+        let camera = &genome.camera;
+        let tone_mapping = &genome.tone_mapping;
+        let pixels = (camera.width * camera.height) as f32;
+
+        node.set_attr("name", name.to_string());
+        node.set_attr("size", format!("{} {}", camera.width, camera.height));
+        node.set_attr("center", format!("{} {}", camera.center_x, camera.center_y));
+        node.set_attr("scale", camera.scale.to_string());
+        node.set_attr("zoom", camera.zoom.to_string());
+        node.set_attr("rotate", camera.rotation.to_degrees().to_string());
+        node.set_attr("quality", (genome.iterations as f32 / pixels).to_string());
+        node.set_attr("seed", genome.seed.to_string());
+        node.set_attr("brightness", tone_mapping.brightness.to_string());
+        node.set_attr("contrast", tone_mapping.contrast.to_string());
+        node.set_attr("gamma", tone_mapping.gamma.to_string());
+        node.set_attr("gamma_threshold", tone_mapping.gamma_threshold.to_string());
+        node.set_attr("vibrancy", tone_mapping.vibrancy.to_string());
+        node.set_attr("highlight_power", tone_mapping.highlight_power.to_string());
+
+        // radius 0 turns DE off in flam3.
+        let de_filter = genome.de_filter.clone().unwrap_or(DensityEstimationFilter { min_radius: 0.0, max_radius: 0.0, curve: 0.0 });
+        node.set_attr("estimator_radius", de_filter.max_radius.to_string());
+        node.set_attr("estimator_minimum", de_filter.min_radius.to_string());
+        node.set_attr("estimator_curve", de_filter.curve.to_string());
+
+        let filter_shape = match genome.oversampling.filter {
+            ReconstructionFilter::Box => "box",
+            ReconstructionFilter::Gaussian => "gaussian",
+            ReconstructionFilter::Mitchell => "mitchell",
+            ReconstructionFilter::Lanczos => "lanczos3"
+        };
+        node.set_attr("supersample", genome.oversampling.factor.to_string());
+        node.set_attr("filter", genome.oversampling.radius.to_string());
+        node.set_attr("filter_shape", filter_shape.to_string());
+
+        // flam3 has no symmetry of its own, symmetry transforms are written out as xforms.
+        let transforms = genome.transforms_with_symmetry();
+        for (idx, transform) in transforms.iter().enumerate() {
+            let mut xform = Self::write_xform("xform", transform, Some(transform.p));
+
+            if let Some(xaos) = &genome.xaos {
+                let row: Vec<String> = (0..transforms.len()).map(|to| xaos.weight(idx, to).to_string()).collect();
+                xform.set_attr("chaos", row.join(" "));
+            }
+
+            node.children.push(xform);
+        }
+
+        if let Some(final_transform) = &genome.final_transform {
+            node.children.push(Self::write_xform("finalxform", final_transform, None));
+        }
+
+        node.children.push(Self::write_palette(&genome.palette));
+        node
+    }
+
+    // final xform has no weight.
+    fn write_xform(tag: &str, transform: &IfsTransform, weight: Option<f32>) -> XmlNode {
+        let mut xform = XmlNode::new(tag);
+
+        if let Some(weight) = weight {
+            xform.set_attr("weight", weight.to_string());
+        }
+
+        xform.set_attr("color", transform.color.to_string());
+        xform.set_attr("color_speed", transform.color_speed.to_string());
+        xform.set_attr("coefs", Self::write_coefs(&transform.mat));
+
+        if let Some(post) = &transform.post {
+            xform.set_attr("post", Self::write_coefs(post));
+        }
+
+        if transform.mutators.is_empty() {
+            xform.set_attr("linear", String::from("1"));
+        }
+
+        for mutator in transform.mutators.iter() {
+            // expression mutators have no flam3 counterpart.
+            let Some((variation, params)) = to_flam3(mutator) else { continue };
+            xform.set_attr(variation, mutator.weight.to_string());
+            params.into_iter().for_each(|(param, value)| xform.set_attr(param, value.to_string()));
+        }
+
+        xform
+    }
+
+    fn write_coefs(mat: &AffineMat) -> String {
+        format!("{} {} {} {} {} {}", mat.a, mat.c, mat.b, mat.d, mat.e, mat.f)
+    }
+
+    fn write_palette(palette: &Palette) -> XmlNode {
+        let mut node = XmlNode::new("palette");
+        node.set_attr("count", Palette::SIZE.to_string());
+        node.set_attr("format", String::from("RGB"));
+
+        // 8 colors per line, as flam3 does.
+        node.text = palette
+            .colors()
+            .chunks(8)
+            .map(|line| {
+                line.iter()
+                    .map(|rgb| rgb.iter().map(|channel| format!("{:02X}", (channel.clamp(0.0, 1.0) * 255.0).round() as u8)).collect::<String>())
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        node
+    }
+
+    fn parse_floats(value: &str) -> Result<Vec<f32>, RepositoryError> {
+        value
+            .split_whitespace()
+            .map(|number| number.parse::<f32>().map_err(|_| RepositoryError::XMLDecoding))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::mutators::MutatorConfig;
+    use crate::ff_repository::xml_helper::XmlHelper;
+    use crate::mutators::flam3::to_flam3;
+    use super::{Flam3Repository, ImportReport};
+
+    const FLAME: &str = r#"<?xml version="1.0"?>
+        <flames>
+           <flame name="test" size="640 480" center="0.5 -0.25" scale="200" rotate="90" quality="10" brightness="3"
+                  estimator_radius="0" supersample="2" filter_shape="lanczos3">
+              <xform weight="0.5" color="0" symmetry="0" coefs="1 2 3 4 5 6" julian="0.5" julian_power="5" hypertile="0.25" post="1 0 0 1 0.5 0"/>
+              <xform weight="0.25" color="1" color_speed="0.1" coefs="0.5 0 0 0.5 0 0" spherical="1" chaos="1 0 1"/>
+              <xform weight="0.25" coefs="1 0 0 1 0 0" linear="1" post="1 0 0 1 0 0"/>
+              <finalxform color="0" symmetry="1" coefs="0 1 -1 0 0 0" spherical="1"/>
+              <palette count="2" format="RGB">FF0000 0000FF</palette>
+           </flame>
+        </flames>"#;
+
+    #[test]
+    fn test_import() {
+        let flames = Flam3Repository::parse(FLAME).unwrap();
+        let flame = &flames[0];
+        let genome = &flame.genome;
+
+        assert_eq!(flame.name, "test");
+        assert_eq!(genome.transforms[0].mat, AffineMat::from(1.0, 3.0, 2.0, 4.0, 5.0, 6.0));
+        assert_eq!(genome.transforms[0].color_speed, 0.5);
+        assert_eq!(genome.transforms[1].p, 0.25);
+        assert!(genome.transforms[2].mutators.is_empty());
+        assert_eq!(genome.transforms[0].post, Some(AffineMat::from(1.0, 0.0, 0.0, 1.0, 0.5, 0.0)));
+        assert_eq!(genome.transforms[2].post, None);
+        assert_eq!(genome.transforms[0].mutators.len(), 1);
+        assert_eq!(genome.transforms[0].mutators[0], MutatorConfig::named(0.5, "julian", &[5.0, 1.0]).unwrap());
+        assert_eq!(genome.transforms[1].mutators[0], MutatorConfig::named(1.0, "spherical", &[]).unwrap());
+
+        assert_eq!((genome.camera.width, genome.camera.height), (640, 480));
+        assert!((genome.camera.rotation - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(genome.iterations, 640 * 480 * 10);
+        assert_eq!(genome.tone_mapping.brightness, 3.0);
+        assert!(genome.de_filter.is_none());
+        assert_eq!(genome.oversampling.factor, 2);
+        assert_eq!(genome.palette.lookup(0.0), [1.0, 0.0, 0.0]);
+
+        assert_eq!(flame.report.unsupported_variations, vec!["hypertile"]);
+        assert!(flame.report.ignored.is_empty());
+
+        let xaos = genome.xaos.as_ref().unwrap();
+        assert_eq!(xaos.weight(1, 1), 0.0);
+        assert_eq!(xaos.weight(0, 1), 1.0);
+
+        let final_transform = genome.final_transform.as_ref().unwrap();
+        assert_eq!(final_transform.mat, AffineMat::from(0.0, -1.0, 1.0, 0.0, 0.0, 0.0));
+        assert_eq!(final_transform.color_speed, 0.0);
+        assert_eq!(final_transform.mutators[0], MutatorConfig::named(1.0, "spherical", &[]).unwrap());
+        assert!(!flame.report.is_exact());
+    }
+
+    #[test]
+    fn test_parse_xform_prefixed_variations() {
+        // wedge_julia and wedge_sph aren't parameters of wedge.
+        let root = XmlHelper::parse(r#"<xform weight="1" coefs="1 0 0 1 0 0" wedge_julia="0.5" wedge_julia_power="2" wedge_sph="1"/>"#).unwrap();
+        let mut report = ImportReport::default();
+        let transform = Flam3Repository::parse_xform(&root.children[0], &mut report).unwrap();
+
+        assert!(report.is_exact(), "{}", report);
+        let mut names: Vec<&str> = transform.mutators.iter().map(|config| config.mutator.name()).collect();
+        names.sort();
+        assert_eq!(names, vec!["wedge_julia", "wedge_sph"]);
+
+        let wedge_julia = transform.mutators.iter().find(|config| config.mutator.name() == "wedge_julia").unwrap();
+        assert_eq!(wedge_julia.weight, 0.5);
+        assert_eq!(to_flam3(wedge_julia).unwrap().1[2], ("wedge_julia_power", 2.0));
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let genome = Flam3Repository::parse(FLAME).unwrap().remove(0).genome;
+        let xml = Flam3Repository::write(&[("again", &genome)]);
+        let flame = Flam3Repository::parse(&xml).unwrap().remove(0);
+
+        assert_eq!(flame.name, "again");
+        assert!(flame.report.is_exact(), "{}", flame.report);
+        assert_eq!(flame.genome.transforms, genome.transforms);
+        assert_eq!(flame.genome.final_transform, genome.final_transform);
+        assert_eq!(flame.genome.xaos.unwrap().weight(1, 1), 0.0);
+        assert_eq!(flame.genome.camera, genome.camera);
+        assert_eq!(flame.genome.iterations, genome.iterations);
+        assert_eq!(flame.genome.palette.lookup_u8(0.3), genome.palette.lookup_u8(0.3));
+    }
+}
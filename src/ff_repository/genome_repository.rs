@@ -0,0 +1,55 @@
+use crate::ds::flame_genome::FlameGenome;
+use crate::ff_repository::json_helper::JsonHelper;
+use crate::ff_repository::repository_error::RepositoryError;
+
+/// Genomes, stored as JSON (see `FlameGenome`).
+pub(crate) struct GenomeRepository;
+
+impl GenomeRepository {
+    pub(crate) fn load(path: &str) -> Result<FlameGenome, RepositoryError> {
+        let json = JsonHelper::read_db(path)?;
+        Self::parse(&json)
+    }
+
+    fn parse(json: &str) -> Result<FlameGenome, RepositoryError> {
+        let genome = JsonHelper::parse_data::<FlameGenome>(json)?;
+
+        let transforms = genome.transforms.len();
+        if genome.xaos.as_ref().is_some_and(|xaos| !xaos.fits(transforms)) {
+            return Err(RepositoryError::XaosSize { transforms });
+        }
+
+        Ok(genome)
+    }
+
+    pub(crate) fn save(genome: &FlameGenome, path: &str) -> Result<(), RepositoryError> {
+        JsonHelper::write_db(path, genome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::flame_genome::FlameGenome;
+    use crate::ds::ifs_transform::IfsTransform;
+    use crate::ds::xaos::Xaos;
+    use crate::ff_repository::repository_error::RepositoryError;
+    use crate::statistics::camera::Camera;
+    use super::GenomeRepository;
+
+    #[test]
+    fn test_xaos_size() {
+        let transforms = vec![
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.5, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 0.5, 1.0, 0.5)
+        ];
+        let mut genome = FlameGenome::new(transforms, Camera::new(0.5, 0.5, 256.0, 0.0, 256, 256), 1_000, 42);
+
+        genome.xaos = Some(Xaos::new(vec![vec![0.0, 1.0], vec![1.0, 0.0]]));
+        assert!(GenomeRepository::parse(&serde_json::to_string(&genome).unwrap()).is_ok());
+
+        genome.xaos = Some(Xaos::new(vec![vec![0.0, 1.0, 1.0]; 3]));
+        let parsed = GenomeRepository::parse(&serde_json::to_string(&genome).unwrap());
+        assert!(matches!(parsed, Err(RepositoryError::XaosSize { transforms: 2 })));
+    }
+}
@@ -1,5 +1,5 @@
-use std::fs::read_to_string;
-use serde::Deserialize;
+use std::fs::{read_to_string, write};
+use serde::{Deserialize, Serialize};
 use crate::ff_repository::repository_error::RepositoryError;
 
 pub(super) struct JsonHelper;
@@ -12,6 +12,11 @@ impl JsonHelper {
         };
     }
 
+    pub(crate) fn write_db<T>(db_path: &str, data: &T) -> Result<(), RepositoryError> where T: Serialize {
+        let json = serde_json::to_string_pretty(data).map_err(|_| RepositoryError::JSONDecoding)?;
+        write(db_path, json).map_err(|_| RepositoryError::FileNotWritten)
+    }
+
     pub(crate) fn parse_data<'de, T>(json: &'de str) -> Result<T, RepositoryError> where T: Deserialize<'de> {
         match serde_json::from_str(&json) as serde_json::Result<T> {
             Ok(parse_result) => { Ok(parse_result) },
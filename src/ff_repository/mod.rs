@@ -1,5 +1,9 @@
 pub(crate) mod presets_repository;
 pub(crate) mod mutator_description_service;
 pub(crate) mod repository_error;
+pub(crate) mod palette_repository;
+pub(crate) mod genome_repository;
+pub(crate) mod flam3_repository;
 
 mod json_helper;
+mod xml_helper;
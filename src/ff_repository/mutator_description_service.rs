@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 use serde::Deserialize;
 use crate::ff_repository::json_helper::JsonHelper;
-use crate::ff_repository::repository_error::RepositoryError;
+use crate::ff_repository::repository_error::{MutatorDescriptionError, RepositoryError};
+use crate::mutators::expression::ExpressionSource;
+use crate::mutators::registry::{MutatorError, MutatorRegistry};
+use crate::mutators::{MutatorConfig, ALL_MUTATOR_DISCRIMINANTS};
 
 #[derive(Deserialize, Default, Clone, Debug)]
 pub(crate) struct MutatorDescription {
@@ -10,24 +13,36 @@ pub(crate) struct MutatorDescription {
     pub is_rand: bool,
     pub requires_affine: bool,
     pub param_order: Option<Vec<String>>,
-    pub params: Option<Vec<MutatorParam>>
+    pub params: Option<Vec<MutatorParam>>,
+    // expression mutators only, see `ExpressionMutator`. Registered on load.
+    #[serde(default)]
+    pub expression: Option<ExpressionSource>
 }
 
 impl MutatorDescription {
-    pub(crate) fn param(&self, name: &str) -> f32 {
-        let screen_name = &self.screen_name;
-        self.params
-            .as_ref()
-            .expect(&format!("No params for mutator {screen_name}"))
+    /// Mutator `enum_id` of the registry, `None` if there is no such mutator.
+    /// `param` - value of the param, given its description (the one of the registry, if this one has none).
+    pub(crate) fn config<F>(&self, weight: f32, mut param: F) -> Option<MutatorConfig> where F: FnMut(&MutatorParam) -> f32 {
+        let mutator = MutatorRegistry::global().read().unwrap().get(self.enum_id)?.clone();
+
+        let params = mutator.params()
             .iter()
-            .find(|param| param.name == name)
-            .expect(&format!("No param with name {name} for mutator {screen_name}"))
-            .default_value
+            .map(|schema| {
+                let desc = self.params
+                    .iter()
+                    .flatten()
+                    .find(|desc| desc.name == schema.name)
+                    .unwrap_or(schema);
+                param(desc)
+            })
+            .collect();
+
+        Some(MutatorConfig::new(weight, mutator, params))
     }
 }
 
 #[derive(Deserialize, Debug, Clone)]
-pub(crate) struct MutatorParam {
+pub struct MutatorParam {
     pub name: String,
     pub lower_bound: f32,
     pub upper_bound: f32,
@@ -45,24 +60,143 @@ impl AsRef<Vec<MutatorDescription>> for MutatorDescriptionService {
 }
 
 impl MutatorDescriptionService {
+    /// Fails, unless each of `ALL_MUTATOR_DISCRIMINANTS` is described once, and each description
+    /// matches its mutator in the registry, see `validate`.
     pub(crate) fn load(db_path: &str) -> Result<Self, RepositoryError> {
         match JsonHelper::read_db(db_path) {
-            Ok(json) => {
-                JsonHelper::parse_data::<HashMap<String, MutatorDescription>>(&json).map(|parsed_data|  {
-                    Self { mutator_desc: Self::transform(&parsed_data) }
-                })
-            }
+            Ok(json) => Self::parse(&json),
             Err(error) => { Err(error) }
         }
     }
 
-    fn transform(mutator_desc: &HashMap<String, MutatorDescription>) -> Vec<MutatorDescription> {
-        let mut result = vec![MutatorDescription::default(); mutator_desc.len()];
+    fn parse(json: &str) -> Result<Self, RepositoryError> {
+        let parsed_data = JsonHelper::parse_data::<HashMap<String, MutatorDescription>>(json)?;
+        Self::validate_ids(&parsed_data).map_err(RepositoryError::MutatorDescription)?;
 
-        mutator_desc.values().for_each(|desc| {
-            let index = desc.enum_id as usize - 1;
-            result[index] = desc.clone();
-        });
+        let mut mutator_desc = Self::transform(&parsed_data);
+        mutator_desc
+            .iter_mut()
+            .try_for_each(|desc| {
+                Self::register(desc, Some(desc.enum_id))
+                    .map_err(|error| MutatorDescriptionError::Registration { name: desc.screen_name.clone(), error })
+            })
+            .map_err(RepositoryError::MutatorDescription)?;
+        mutator_desc
+            .iter()
+            .try_for_each(Self::validate)
+            .map_err(RepositoryError::MutatorDescription)?;
+
+        Ok(Self { mutator_desc })
+    }
+
+    // each built-in, searched over, once.
+    fn validate_ids(mutator_desc: &HashMap<String, MutatorDescription>) -> Result<(), MutatorDescriptionError> {
+        let mut names_by_id: HashMap<u8, Vec<String>> = HashMap::new();
+        mutator_desc
+            .values()
+            .for_each(|desc| names_by_id.entry(desc.enum_id).or_default().push(desc.screen_name.clone()));
+
+        if let Some(id) = ALL_MUTATOR_DISCRIMINANTS.clone().find(|id| !names_by_id.contains_key(id)) {
+            return Err(MutatorDescriptionError::Missing(id));
+        }
+        let mut duplicates: Vec<(u8, Vec<String>)> = names_by_id.into_iter().filter(|(_, names)| names.len() > 1).collect();
+        duplicates.sort_by_key(|(id, _)| *id);
+        match duplicates.into_iter().next() {
+            Some((id, mut names)) => {
+                names.sort();
+                Err(MutatorDescriptionError::Duplicate(id, names))
+            }
+            None => Ok(())
+        }
+    }
+
+    // against the mutator of the registry: name, params (and their order), defaults within bounds, flags.
+    fn validate(desc: &MutatorDescription) -> Result<(), MutatorDescriptionError> {
+        let registry = MutatorRegistry::global().read().unwrap();
+        let name = desc.screen_name.clone();
+        let Some(mutator) = registry.get(desc.enum_id) else {
+            return Err(MutatorDescriptionError::Unknown(desc.enum_id, name));
+        };
+
+        if mutator.name() != name {
+            return Err(MutatorDescriptionError::NameMismatch { id: desc.enum_id, expected: mutator.name().to_string(), found: name });
+        }
+
+        let expected: Vec<String> = mutator.params().iter().map(|param| param.name.clone()).collect();
+        let params = desc.params.as_deref().unwrap_or_default();
+        let found: Vec<String> = params.iter().map(|param| param.name.clone()).collect();
+        // param_order of the mutators w/o params may name the affine coefficients they read (i.e. waves).
+        let order_mismatch = !params.is_empty() && desc.param_order.as_ref().is_some_and(|order| *order != found);
+        if found != expected || order_mismatch {
+            let found = if order_mismatch { desc.param_order.clone().unwrap_or_default() } else { found };
+            return Err(MutatorDescriptionError::ParamsMismatch { name, expected, found });
+        }
+
+        if let Some(param) = params.iter().find(|param| !(param.lower_bound..=param.upper_bound).contains(&param.default_value)) {
+            return Err(MutatorDescriptionError::DefaultOutOfBounds { name, param: param.name.clone() });
+        }
+
+        if desc.is_rand != mutator.is_rand() {
+            return Err(MutatorDescriptionError::FlagMismatch { name, flag: "is_rand" });
+        }
+        if desc.requires_affine != mutator.requires_affine() {
+            return Err(MutatorDescriptionError::FlagMismatch { name, flag: "requires_affine" });
+        }
+
+        Ok(())
+    }
+
+    /// Adds an expression mutator under a free id of the registry, returns its `enum_id`.
+    /// `params` - in the order, the expression params are given values in.
+    pub(crate) fn register_expression(
+        &mut self,
+        name: &str,
+        expression: ExpressionSource,
+        params: Vec<MutatorParam>
+    ) -> Result<u8, MutatorError> {
+        if self.mutator_desc.iter().any(|desc| desc.screen_name == name) {
+            return Err(MutatorError::NameTaken(name.to_string()));
+        }
+
+        let mut desc = MutatorDescription {
+            screen_name: name.to_string(),
+            param_order: Some(params.iter().map(|param| param.name.clone()).collect()),
+            params: Some(params),
+            expression: Some(expression),
+            ..MutatorDescription::default()
+        };
+        Self::register(&mut desc, None)?;
+
+        let enum_id = desc.enum_id;
+        self.mutator_desc.push(desc);
+        Ok(enum_id)
+    }
+
+    // no-op for the built-ins. The same expression under the same name is registered once
+    // (i.e. the DB is loaded again), unless it came w. a genome: that one has no bounds, and is replaced.
+    fn register(desc: &mut MutatorDescription, id: Option<u8>) -> Result<(), MutatorError> {
+        let Some(expression) = desc.expression.clone() else { return Ok(()) };
+
+        let mut registry = MutatorRegistry::global().write().unwrap();
+        let mutator = match registry.by_name(&desc.screen_name) {
+            Some(mutator) if mutator.expression() == Some(&expression)
+                && id.is_none_or(|id| id == mutator.id())
+                && !registry.is_provisional(mutator.id()) => mutator.clone(),
+            _ => {
+                let params = desc.params.clone().unwrap_or_default();
+                registry.register_expression(id, &desc.screen_name, expression, params)?
+            }
+        };
+
+        desc.enum_id = mutator.id();
+        desc.is_rand = mutator.is_rand();
+        desc.requires_affine = mutator.requires_affine();
+        Ok(())
+    }
+
+    fn transform(mutator_desc: &HashMap<String, MutatorDescription>) -> Vec<MutatorDescription> {
+        let mut result: Vec<MutatorDescription> = mutator_desc.values().cloned().collect();
+        result.sort_by_key(|desc| desc.enum_id);
 
         result
     }
@@ -71,7 +205,112 @@ impl MutatorDescriptionService {
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
-    use crate::ff_repository::mutator_description_service::MutatorDescription;
+    use crate::ff_repository::mutator_description_service::{MutatorDescription, MutatorDescriptionService, MutatorParam};
+    use crate::ff_repository::repository_error::{MutatorDescriptionError, RepositoryError};
+    use crate::mutators::expression::{ExpressionError, ExpressionSource};
+    use crate::mutators::MutatorConfig;
+    use crate::mutators::registry::{MutatorError, MutatorRegistry};
+
+    #[test]
+    fn test_register_expression() {
+        let mut service = MutatorDescriptionService { mutator_desc: Vec::new() };
+        let params = vec![MutatorParam { name: String::from("k"), lower_bound: 0.0, upper_bound: 2.0, default_value: 1.0 }];
+
+        let enum_id = service
+            .register_expression("wobble", ExpressionSource::new("x + k*sin(y*psi)", "y"), params.clone())
+            .unwrap();
+        // after the built-ins.
+        assert!(enum_id > 98);
+        assert_eq!(MutatorRegistry::global().read().unwrap().get(enum_id).unwrap().name(), "wobble");
+
+        let desc = &service.as_ref()[0];
+        assert!(desc.is_rand && !desc.requires_affine);
+        assert_eq!(desc.config(1.0, |param| param.default_value).unwrap().params, vec![1.0]);
+
+        assert!(service.register_expression("wobble", ExpressionSource::new("x", "y"), Vec::new()).is_err());
+        assert!(service.register_expression("broken", ExpressionSource::new("x + k", "y"), Vec::new()).is_err());
+        assert_eq!(service.as_ref().len(), 1);
+    }
+
+    #[test]
+    fn test_described_replaces_provisional() {
+        // a genome's mutator, which isn't described yet.
+        let saved: MutatorConfig = serde_json::from_str(
+            r#"{"weight": 1.0, "mutator": "ripple", "params": {"k": 0.5}, "expression": {"x": "x + k*sin(y)", "y": "y"}}"#
+        ).unwrap();
+        assert!(MutatorRegistry::global().read().unwrap().is_provisional(saved.mutator.id()));
+        assert_eq!(saved.mutator.params()[0].upper_bound, 0.5);
+
+        let mut service = MutatorDescriptionService { mutator_desc: Vec::new() };
+        let params = vec![MutatorParam { name: String::from("k"), lower_bound: 0.0, upper_bound: 2.0, default_value: 1.0 }];
+        let enum_id = service
+            .register_expression("ripple", ExpressionSource::new("x + k*sin(y)", "y"), params)
+            .unwrap();
+
+        let registry = MutatorRegistry::global().read().unwrap();
+        let described = registry.by_name("ripple").unwrap();
+        assert_eq!(described.id(), enum_id);
+        assert!(!registry.is_provisional(enum_id));
+        assert_eq!(described.params()[0].upper_bound, 2.0);
+        assert_eq!(described.expression(), saved.mutator.expression());
+    }
+
+    // number of descriptions.
+    fn parse(json: &serde_json::Value) -> Result<usize, MutatorDescriptionError> {
+        match MutatorDescriptionService::parse(&json.to_string()) {
+            Ok(service) => Ok(service.as_ref().len()),
+            Err(RepositoryError::MutatorDescription(error)) => Err(error),
+            Err(error) => panic!("{error}")
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let json: serde_json::Value = serde_json::from_str(include_str!("../mut_desc.json")).unwrap();
+        assert_eq!(parse(&json).unwrap(), 98);
+
+        let mut missing = json.clone();
+        missing.as_object_mut().unwrap().remove("swirl");
+        assert_eq!(parse(&missing).unwrap_err(), MutatorDescriptionError::Missing(3));
+
+        let mut duplicate = json.clone();
+        duplicate["swirl2"] = duplicate["swirl"].clone();
+        assert_eq!(parse(&duplicate).unwrap_err(), MutatorDescriptionError::Duplicate(3, vec![String::from("swirl"), String::from("swirl")]));
+
+        let mut renamed = json.clone();
+        renamed["julian"]["params"][1]["name"] = serde_json::json!("distance");
+        assert!(matches!(parse(&renamed).unwrap_err(), MutatorDescriptionError::ParamsMismatch { name, .. } if name == "julian"));
+
+        let mut reordered = json.clone();
+        reordered["julian"]["param_order"] = serde_json::json!(["dist", "power"]);
+        assert!(matches!(parse(&reordered).unwrap_err(), MutatorDescriptionError::ParamsMismatch { .. }));
+
+        let mut out_of_bounds = json.clone();
+        out_of_bounds["julian"]["params"][0]["default_value"] = serde_json::json!(11.0);
+        assert_eq!(
+            parse(&out_of_bounds).unwrap_err(),
+            MutatorDescriptionError::DefaultOutOfBounds { name: String::from("julian"), param: String::from("power") }
+        );
+
+        let mut flag = json.clone();
+        flag["swirl"]["is_rand"] = serde_json::json!(true);
+        assert_eq!(parse(&flag).unwrap_err(), MutatorDescriptionError::FlagMismatch { name: String::from("swirl"), flag: "is_rand" });
+
+        let mut broken = json.clone();
+        broken["broken_expression"] = serde_json::json!({
+            "enum_id": 251, "screen_name": "broken_expression", "is_rand": false, "requires_affine": false,
+            "expression": { "x": "x + z", "y": "y" }
+        });
+        let MutatorDescriptionError::Registration { name, error: MutatorError::Expression(error) } = parse(&broken).unwrap_err() else {
+            panic!("expected a registration error");
+        };
+        assert_eq!(name, "broken_expression");
+        assert_eq!(error, ExpressionError { position: 4, message: String::from("x: unknown variable z") });
+
+        let mut unknown = json;
+        unknown["hypertile"] = serde_json::json!({ "enum_id": 250, "screen_name": "hypertile", "is_rand": false, "requires_affine": false });
+        assert_eq!(parse(&unknown).unwrap_err(), MutatorDescriptionError::Unknown(250, String::from("hypertile")));
+    }
 
     #[test]
     fn test_deserialize() {
@@ -0,0 +1,256 @@
+use std::path::Path;
+use crate::alg::color_steal::{ColorSteal, StealMethod};
+use crate::ds::palette::Palette;
+use crate::ff_repository::json_helper::JsonHelper;
+use crate::ff_repository::repository_error::RepositoryError;
+use crate::ff_repository::xml_helper::{XmlHelper, XmlNode};
+
+/// Palettes, loaded from:
+/// 1. flam3 palette XML (`<palette number="0" name="..." data="00RRGGBB..."/>`, as in flam3-palettes.xml).
+/// 2. Palettes embedded into flame XML (`<palette count="256" format="RGB">RRGGBB...</palette>`, or `<color index="0" rgb="r g b"/>`).
+/// 3. Plain text gradients, one color per line: `r g b` (0..255) or `#RRGGBB`. Lines starting with `;` or `//` are comments.
+pub(crate) struct PaletteRepository {
+    palettes: Vec<Palette>
+}
+
+impl AsRef<Vec<Palette>> for PaletteRepository {
+    fn as_ref(&self) -> &Vec<Palette> {
+        &self.palettes
+    }
+}
+
+impl PaletteRepository {
+    pub(crate) fn builtin() -> Self {
+        Self {
+            palettes: Palette::BUILTIN_NAMES
+                .iter()
+                .filter_map(|name| Palette::builtin(name))
+                .collect()
+        }
+    }
+
+    pub(crate) fn load(db_path: &str) -> Result<Self, RepositoryError> {
+        let data = JsonHelper::read_db(db_path)?;
+        let name = Path::new(db_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("gradient");
+
+        let palettes = if data.trim_start().starts_with('<') {
+            Self::parse_xml(&data)?
+        } else {
+            vec![Self::parse_gradient(name, &data)?]
+        };
+
+        Ok(Self { palettes })
+    }
+
+    /// Color-steals a palette from an image (anything `image` can open, i.e. PNG or JPEG).
+    pub(crate) fn steal(img_path: &str, method: &StealMethod) -> Result<Palette, RepositoryError> {
+        let img = match image::open(img_path) {
+            Ok(img) => img.to_rgb8(),
+            Err(image::ImageError::IoError(_)) => return Err(RepositoryError::FileNotFound),
+            Err(_) => return Err(RepositoryError::ImageDecoding)
+        };
+
+        let name = Path::new(img_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("stolen");
+
+        Ok(ColorSteal::steal(name, &img, method))
+    }
+
+    pub(crate) fn find_palette_by(&self, name: &str) -> Option<&Palette> {
+        self.palettes.iter().find(|palette| palette.name == name)
+    }
+
+    pub(crate) fn parse_xml(xml: &str) -> Result<Vec<Palette>, RepositoryError> {
+        let root = XmlHelper::parse(xml)?;
+
+        let mut palette_nodes = Vec::new();
+        root.find_all("palette", &mut palette_nodes);
+
+        let mut palettes = palette_nodes
+            .into_iter()
+            .map(Self::parse_palette_node)
+            .collect::<Result<Vec<Palette>, RepositoryError>>()?;
+
+        // flame with palette given as <color/> elements.
+        let mut flames = Vec::new();
+        root.find_all("flame", &mut flames);
+        for flame in flames {
+            if flame.children_named("color").next().is_some() {
+                palettes.push(Self::parse_color_elements(flame)?);
+            }
+        }
+
+        if palettes.is_empty() {
+            return Err(RepositoryError::XMLDecoding);
+        }
+
+        Ok(palettes)
+    }
+
+    /// Palette of a single `<flame>` node, either of the embedded forms.
+    pub(crate) fn parse_flame_palette(flame: &XmlNode) -> Result<Palette, RepositoryError> {
+        if let Some(palette) = flame.children_named("palette").next() {
+            return Self::parse_palette_node(palette);
+        }
+
+        Self::parse_color_elements(flame)
+    }
+
+    fn parse_palette_node(node: &XmlNode) -> Result<Palette, RepositoryError> {
+        let name = node
+            .attr("name")
+            .map(|name| name.to_string())
+            .or_else(|| node.attr("number").map(|number| format!("flam3-{number}")))
+            .unwrap_or_else(|| String::from("embedded"));
+
+        // flam3-palettes.xml: 00RRGGBB, flame files: RRGGBB (or RGBA with format="RGBA").
+        let (hex, stride, skip) = match node.attr("data") {
+            Some(data) => (data, 8, 2),
+            None if node.attr("format").map(|f| f.eq_ignore_ascii_case("RGBA")).unwrap_or(false) => (node.text.as_str(), 8, 0),
+            None => (node.text.as_str(), 6, 0)
+        };
+
+        let colors = Self::parse_hex_run(hex, stride, skip)?;
+        Ok(Palette::new(&name, colors))
+    }
+
+    fn parse_color_elements(flame: &XmlNode) -> Result<Palette, RepositoryError> {
+        let mut colors = vec![[0.0; 3]; Palette::SIZE];
+        let mut count = 0;
+
+        for color in flame.children_named("color") {
+            let index = color.attr_f32("index").ok_or(RepositoryError::XMLDecoding)? as usize;
+            let rgb = color.attr("rgb").ok_or(RepositoryError::XMLDecoding)?;
+            let rgb = Self::parse_rgb_triplet(rgb).ok_or(RepositoryError::XMLDecoding)?;
+
+            if index >= Palette::SIZE {
+                return Err(RepositoryError::XMLDecoding);
+            }
+
+            colors[index] = rgb;
+            count += 1;
+        }
+
+        if count == 0 {
+            return Err(RepositoryError::XMLDecoding);
+        }
+
+        Ok(Palette::new(flame.attr("name").unwrap_or("embedded"), colors))
+    }
+
+    pub(crate) fn parse_gradient(name: &str, text: &str) -> Result<Palette, RepositoryError> {
+        let colors = text
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with(';') && !line.starts_with("//"))
+            .map(|line| {
+                let color = if let Some(hex) = line.strip_prefix('#') {
+                    Self::parse_hex_run(hex, 6, 0).ok().filter(|c| c.len() == 1).map(|c| c[0])
+                } else {
+                    Self::parse_rgb_triplet(line)
+                };
+
+                color.ok_or(RepositoryError::GradientDecoding)
+            })
+            .collect::<Result<Vec<[f32; 3]>, RepositoryError>>()?;
+
+        if colors.is_empty() {
+            return Err(RepositoryError::GradientDecoding);
+        }
+
+        Ok(Palette::new(name, colors))
+    }
+
+    // "r g b", 0..255
+    fn parse_rgb_triplet(triplet: &str) -> Option<[f32; 3]> {
+        let channels: Vec<f32> = triplet
+            .split_whitespace()
+            .map(|channel| channel.parse::<f32>())
+            .collect::<Result<Vec<f32>, _>>()
+            .ok()?;
+
+        if channels.len() != 3 {
+            return None;
+        }
+
+        Some([channels[0] / 255.0, channels[1] / 255.0, channels[2] / 255.0])
+    }
+
+    // `stride` hex digits per color, first `skip` of which are ignored.
+    fn parse_hex_run(hex: &str, stride: usize, skip: usize) -> Result<Vec<[f32; 3]>, RepositoryError> {
+        let digits: Vec<u8> = hex
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .map(|ch| ch.to_digit(16).map(|digit| digit as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(RepositoryError::XMLDecoding)?;
+
+        if digits.is_empty() || !digits.len().is_multiple_of(stride) {
+            return Err(RepositoryError::XMLDecoding);
+        }
+
+        Ok(digits
+            .chunks(stride)
+            .map(|chunk| {
+                let channel = |idx: usize| (chunk[skip + 2 * idx] * 16 + chunk[skip + 2 * idx + 1]) as f32 / 255.0;
+                [channel(0), channel(1), channel(2)]
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alg::color_steal::StealMethod;
+    use crate::ds::palette::Palette;
+    use crate::ff_repository::repository_error::RepositoryError;
+    use super::PaletteRepository;
+
+    #[test]
+    fn test_parse_flam3_palettes() {
+        let data = "00ff0000".repeat(128) + &"000000ff".repeat(128);
+        let xml = format!(r#"<palettes><palette number="7" name="red-blue" data="{data}"/></palettes>"#);
+
+        let palettes = PaletteRepository::parse_xml(&xml).expect("Can't parse palettes!");
+        assert_eq!(palettes.len(), 1);
+        assert_eq!(palettes[0].name, "red-blue");
+        assert_eq!(palettes[0].lookup(0.0), [1.0, 0.0, 0.0]);
+        assert_eq!(palettes[0].lookup(1.0), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_parse_embedded_palettes() {
+        let hex = "00FF00".repeat(Palette::SIZE);
+        let xml = format!(r#"<flame name="f"><palette count="256" format="RGB">{hex}</palette></flame>"#);
+        let palettes = PaletteRepository::parse_xml(&xml).unwrap();
+        assert_eq!(palettes[0].lookup(0.3), [0.0, 1.0, 0.0]);
+
+        let xml = r#"<flame name="g"><color index="0" rgb="255 255 255"/><color index="255" rgb="255 255 255"/></flame>"#;
+        let palettes = PaletteRepository::parse_xml(xml).unwrap();
+        assert_eq!(palettes[0].name, "g");
+        assert_eq!(palettes[0].lookup(1.0), [1.0, 1.0, 1.0]);
+        assert_eq!(palettes[0].lookup(0.5), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_parse_gradient() {
+        let text = "; comment\n0 0 0\n#FFFFFF\n";
+        let palette = PaletteRepository::parse_gradient("bw", text).expect("Can't parse gradient!");
+
+        assert_eq!(palette.lookup(0.0), [0.0, 0.0, 0.0]);
+        assert_eq!(palette.lookup(1.0), [1.0, 1.0, 1.0]);
+
+        assert!(PaletteRepository::parse_gradient("bad", "1 2").is_err());
+    }
+
+    #[test]
+    fn test_steal_missing_image() {
+        let res = PaletteRepository::steal("no_such_image.png", &StealMethod::diagonal());
+        assert!(matches!(res, Err(RepositoryError::FileNotFound)));
+    }
+}
@@ -10,23 +10,24 @@ pub(crate) struct PresetsRepository {
 
 impl PresetsRepository {
     pub(crate) fn load(db_path: &str) -> Result<Self, RepositoryError> {
-        // - TODO: how to forward error btw maps?
         match JsonHelper::read_db(db_path) {
-            Ok(json) => {
-                JsonHelper::parse_data::<Vec<AffIfs>>(&json)
-                    .map(|parse_result| {
-                        let mut self_ = Self {
-                            affine_presets: parse_result,
-                            flatted: Vec::<IfsTransform>::new()
-                        };
-                        self_.post_process();
-                        self_
-                    })
-            },
+            Ok(json) => Self::parse(&json),
             Err(error) => { Err(error) }
         }
     }
 
+    fn parse(json: &str) -> Result<Self, RepositoryError> {
+        let affine_presets = JsonHelper::parse_data::<Vec<AffIfs>>(json)?;
+
+        if let Some(ifs) = affine_presets.iter().find(|ifs| ifs.xaos.as_ref().is_some_and(|xaos| !xaos.fits(ifs.transforms.len()))) {
+            return Err(RepositoryError::XaosSize { transforms: ifs.transforms.len() });
+        }
+
+        let mut self_ = Self { affine_presets, flatted: Vec::<IfsTransform>::new() };
+        self_.post_process();
+        Ok(self_)
+    }
+
     pub(crate) fn find_ifs_by(&self, name: &str) -> Option<&AffIfs> {
         self.affine_presets.iter().find(|ifs| ifs.name == name)
     }
@@ -35,8 +36,8 @@ impl PresetsRepository {
         for affine_ifs in &mut self.affine_presets {
             affine_ifs.prepare_preset_for_chaos_game();
 
-            // - WARNING: it's important that we add those to the flatted array only after transforms in the ifs
-            // are sorted. Need to introduce some sort of lock on this order.
+            // - WARNING: ranks of the combinations index into the flatted array, so
+            // its order (the order of the presets file) must stay put.
             self.flatted.append(&mut affine_ifs.transforms.clone());
         }
     }
@@ -45,6 +46,8 @@ impl PresetsRepository {
 #[cfg(test)]
 mod tests {
     use crate::ds::aff_ifs::AffIfs;
+    use crate::ff_repository::repository_error::RepositoryError;
+    use super::PresetsRepository;
 
     #[test]
     fn test_json_parse() {
@@ -64,7 +67,8 @@ mod tests {
                     {  "a": 0.5, "b": -0.35, "c": -0.5, "d": 0.35, "e": 0.35, "f": 0, "p": 0.33 },
                     {  "a": 0.35, "b": -0.35, "c": 0.5, "d": 0.35, "e": 0.5, "f": 0, "p": 0.33 },
                     {  "a": 0.35, "b": -0.35, "c": 0, "d": 0.35, "e": 0.35, "f": 0.5, "p": 0.33 }
-                ]
+                ],
+                "xaos": [[0, 1, 1], [1, 0, 1], [1, 1, 0]]
             }
         ]
         "#;
@@ -76,5 +80,9 @@ mod tests {
         };
 
         eprintln!("{:?}", ifs);
+
+        assert_eq!(PresetsRepository::parse(json).unwrap().flatted.len(), 6);
+        let mismatched = json.replace("[[0, 1, 1], [1, 0, 1], [1, 1, 0]]", "[[0, 1], [1, 0]]");
+        assert!(matches!(PresetsRepository::parse(&mismatched), Err(RepositoryError::XaosSize { transforms: 3 })));
     }
 }
@@ -1,12 +1,39 @@
 use std::error::Error;
 use std::fmt::Display;
+use crate::mutators::registry::MutatorError;
 
 #[derive(Debug)]
 pub(crate) enum RepositoryError {
     FileNotFound,
+    FileNotWritten,
 
     // wtf is trait object
     JSONDecoding,
+    XMLDecoding,
+    GradientDecoding,
+    ImageDecoding,
+    MutatorDescription(MutatorDescriptionError),
+    /// xaos isn't transforms x transforms.
+    XaosSize { transforms: usize },
+}
+
+/// `mut_desc.json` doesn't match the mutators of the registry.
+#[derive(Debug, PartialEq)]
+pub(crate) enum MutatorDescriptionError {
+    /// of `ALL_MUTATOR_DISCRIMINANTS`.
+    Missing(u8),
+    /// id and the (sorted) names it's described under.
+    Duplicate(u8, Vec<String>),
+    /// no mutator w. the id (and no expression).
+    Unknown(u8, String),
+    NameMismatch { id: u8, expected: String, found: String },
+    /// param names, in order.
+    ParamsMismatch { name: String, expected: Vec<String>, found: Vec<String> },
+    DefaultOutOfBounds { name: String, param: String },
+    /// `is_rand` or `requires_affine`.
+    FlagMismatch { name: String, flag: &'static str },
+    /// expression mutator can't be registered (i.e. doesn't compile).
+    Registration { name: String, error: MutatorError },
 }
 
 impl Display for RepositoryError {
@@ -0,0 +1,229 @@
+use crate::ff_repository::repository_error::RepositoryError;
+
+/// Just enough XML to read and write flam3 files: elements, attributes and text.
+/// No namespaces, no DTDs, no CDATA.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct XmlNode {
+    pub(crate) name: String,
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) children: Vec<XmlNode>,
+    pub(crate) text: String
+}
+
+impl XmlNode {
+    pub(crate) fn new(name: &str) -> Self {
+        Self { name: name.to_string(), ..Default::default() }
+    }
+
+    pub(crate) fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn attr_f32(&self, name: &str) -> Option<f32> {
+        self.attr(name).and_then(|value| value.trim().parse::<f32>().ok())
+    }
+
+    pub(crate) fn set_attr(&mut self, name: &str, value: String) {
+        self.attributes.push((name.to_string(), value));
+    }
+
+    pub(crate) fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Depth-first search for all the elements with a given name (including self).
+    pub(crate) fn find_all<'a>(&'a self, name: &str, found: &mut Vec<&'a XmlNode>) {
+        if self.name == name {
+            found.push(self);
+        }
+
+        self.children.iter().for_each(|child| child.find_all(name, found));
+    }
+}
+
+pub(super) struct XmlHelper;
+
+impl XmlHelper {
+    /// Returns a synthetic root node, whose children are the top-level elements of the document.
+    pub(crate) fn parse(xml: &str) -> Result<XmlNode, RepositoryError> {
+        let mut stack = vec![XmlNode::new("")];
+        let mut rest = xml;
+
+        while let Some(open) = rest.find('<') {
+            let text = &rest[..open];
+            if !text.trim().is_empty() {
+                stack.last_mut().unwrap().text.push_str(&Self::unescape(text.trim()));
+            }
+            rest = &rest[open..];
+
+            // prolog, comments, doctype - skip.
+            if rest.starts_with("<?") || rest.starts_with("<!") {
+                let terminator = if rest.starts_with("<!--") { "-->" } else { ">" };
+                let end = rest.find(terminator).ok_or(RepositoryError::XMLDecoding)?;
+                rest = &rest[end + terminator.len()..];
+                continue;
+            }
+
+            let end = Self::tag_end(rest).ok_or(RepositoryError::XMLDecoding)?;
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                let node = stack.pop().ok_or(RepositoryError::XMLDecoding)?;
+                if node.name != name.trim() || stack.is_empty() {
+                    return Err(RepositoryError::XMLDecoding);
+                }
+                stack.last_mut().unwrap().children.push(node);
+                continue;
+            }
+
+            let self_closing = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let node = Self::parse_tag(tag)?;
+
+            if self_closing {
+                stack.last_mut().unwrap().children.push(node);
+            } else {
+                stack.push(node);
+            }
+        }
+
+        if stack.len() != 1 {
+            return Err(RepositoryError::XMLDecoding);
+        }
+
+        Ok(stack.pop().unwrap())
+    }
+
+    pub(crate) fn write(node: &XmlNode) -> String {
+        let mut res = String::from("<?xml version=\"1.0\"?>\n");
+        Self::write_(node, 0, &mut res);
+        res
+    }
+
+    fn write_(node: &XmlNode, depth: usize, out: &mut String) {
+        let indent = "   ".repeat(depth);
+        out.push_str(&format!("{indent}<{}", node.name));
+
+        for (key, value) in node.attributes.iter() {
+            out.push_str(&format!(" {key}=\"{}\"", Self::escape(value)));
+        }
+
+        if node.children.is_empty() && node.text.is_empty() {
+            out.push_str("/>\n");
+            return;
+        }
+
+        out.push_str(">\n");
+        if !node.text.is_empty() {
+            out.push_str(&format!("{}\n", Self::escape(&node.text)));
+        }
+
+        node.children.iter().for_each(|child| Self::write_(child, depth + 1, out));
+        out.push_str(&format!("{indent}</{}>\n", node.name));
+    }
+
+    // '>' may legally occur inside of the quoted attribute values.
+    fn tag_end(tag: &str) -> Option<usize> {
+        let mut quote: Option<char> = None;
+
+        for (idx, ch) in tag.char_indices() {
+            match (quote, ch) {
+                (None, '"') | (None, '\'') => quote = Some(ch),
+                (Some(q), _) if q == ch => quote = None,
+                (None, '>') => return Some(idx),
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    fn parse_tag(tag: &str) -> Result<XmlNode, RepositoryError> {
+        let tag = tag.trim();
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut node = XmlNode::new(&tag[..name_end]);
+        let mut rest = tag[name_end..].trim_start();
+
+        while !rest.is_empty() {
+            let eq = rest.find('=').ok_or(RepositoryError::XMLDecoding)?;
+            let key = rest[..eq].trim();
+            rest = rest[eq + 1..].trim_start();
+
+            let quote = rest.chars().next().ok_or(RepositoryError::XMLDecoding)?;
+            if quote != '"' && quote != '\'' {
+                return Err(RepositoryError::XMLDecoding);
+            }
+
+            let value_end = rest[1..].find(quote).ok_or(RepositoryError::XMLDecoding)? + 1;
+            node.set_attr(key, Self::unescape(&rest[1..value_end]));
+            rest = rest[value_end + 1..].trim_start();
+        }
+
+        Ok(node)
+    }
+
+    fn escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    fn unescape(value: &str) -> String {
+        value
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XmlHelper;
+
+    #[test]
+    fn test_parse_nested() {
+        let xml = r#"<?xml version="1.0"?>
+        <!-- flames -->
+        <flames name="test">
+            <flame name="a &amp; b" size="10 10">
+                <xform weight="0.5" coefs="1 0 0 1 0 0"/>
+                <palette count="1" format="RGB">FF0000</palette>
+            </flame>
+        </flames>
+        "#;
+
+        let root = XmlHelper::parse(xml).expect("Can't parse XML!");
+        let flames = &root.children[0];
+        assert_eq!(flames.name, "flames");
+
+        let flame = flames.children_named("flame").next().expect("No flame!");
+        assert_eq!(flame.attr("name"), Some("a & b"));
+        assert_eq!(flame.children_named("xform").next().unwrap().attr_f32("weight"), Some(0.5));
+        assert_eq!(flame.children_named("palette").next().unwrap().text, "FF0000");
+    }
+
+    #[test]
+    fn test_write_parse_round_trip() {
+        let root = XmlHelper::parse(r#"<a x="1"><b y="&quot;2&quot;"/><c>text</c></a>"#).unwrap();
+        let written = XmlHelper::write(&root.children[0]);
+        let reparsed = XmlHelper::parse(&written).unwrap();
+
+        let a = &reparsed.children[0];
+        assert_eq!(a.attr("x"), Some("1"));
+        assert_eq!(a.children[0].attr("y"), Some("\"2\""));
+        assert_eq!(a.children[1].text, "text");
+    }
+
+    #[test]
+    fn test_unbalanced() {
+        assert!(XmlHelper::parse("<a><b></a>").is_err());
+    }
+}
@@ -3,6 +3,9 @@ use std::ops::RangeInclusive;
 use image::{Rgba, RgbaImage};
 
 use crate::{ds::array_2d::{Array2D, Index2D}, util};
+use crate::ds::color_histogram::ColorHistogram;
+use crate::ds::palette::Palette;
+use crate::tone_mapping::ToneMapping;
 
 pub(crate) struct RgbRenderer;
 
@@ -55,6 +58,65 @@ impl RgbRenderer {
         return img;
     }
 
+    /// Alpha is computed the same way as in `img_bw_simple`,
+    /// color is looked up in the palette by the average color coordinate of the cell.
+    pub(crate) fn img_color(hist: &ColorHistogram, palette: &Palette) -> RgbaImage {
+        let mut img = RgbaImage::new(hist.width() as u32, hist.height() as u32);
+
+        let width = hist.width();
+        let height = hist.height();
+        let density = hist.density();
+        let log_max = Self::find_log_max(&density);
+
+        for x in 0..width {
+            for y in 0..height {
+                let index = Index2D::from(x, y);
+                let density_val = density[index];
+
+                let color = if density_val > f32::EPSILON {
+                    let alpha = Self::density_to_alpha(density_val, log_max);
+                    let [r, g, b] = palette.lookup_u8(hist.color(index));
+
+                    Rgba::from([r, g, b, (255.0 * alpha).round() as u8])
+                } else {
+                    Rgba::from([0; 4])
+                };
+
+                img.put_pixel(
+                    (width - 1 - x) as u32,
+                    (height - 1 - y) as u32,
+                    color
+                );
+            }
+        }
+
+        img
+    }
+
+    /// FLAM3 style: log-density, gamma, vibrancy and highlight power, see `ToneMapping`.
+    pub(crate) fn img_tone_mapped(hist: &ColorHistogram, palette: &Palette, tone_mapping: &ToneMapping) -> RgbaImage {
+        let mut img = RgbaImage::new(hist.width() as u32, hist.height() as u32);
+
+        let width = hist.width();
+        let height = hist.height();
+        let (k1, k2) = tone_mapping.log_scale_factors(hist.total_hits(), width * height);
+
+        for x in 0..width {
+            for y in 0..height {
+                let index = Index2D::from(x, y);
+                let rgba = tone_mapping.map(hist.hits(index), palette.lookup(hist.color(index)), k1, k2);
+
+                img.put_pixel(
+                    (width - 1 - x) as u32,
+                    (height - 1 - y) as u32,
+                    Rgba::from(rgba.map(|channel| (255.0 * channel).round() as u8))
+                );
+            }
+        }
+
+        img
+    }
+
     // - TODO: refactor.
     pub(crate) fn img_bw_(array: &Array2D) -> RgbaImage {
         let mut img = RgbaImage::new(array.width() as u32, array.height() as u32);
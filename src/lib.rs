@@ -0,0 +1,27 @@
+// Library target, so that other crates can add their own mutators: implement `Mutator`,
+// and `register` it in `MutatorRegistry::global()`. Genomes refer to it by name, as to the built-ins.
+//
+// Modules are the binary's (see `main.rs`), only the mutator API is exported - the rest is there
+// for the mutators to build against.
+#![allow(dead_code)]
+
+mod alg;
+mod chaos_game;
+mod ff_repository;
+mod statistics;
+mod util;
+mod ds;
+mod frac_render;
+mod mutators;
+mod usecase;
+mod modnar;
+mod parallel_chaos_game;
+mod tone_mapping;
+
+pub use ds::affine_mat::AffineMat;
+pub use ds::point::Point;
+pub use ff_repository::mutator_description_service::MutatorParam;
+pub use modnar::Modnar;
+pub use mutators::Mutator;
+pub use mutators::expression::{ExpressionError, ExpressionSource};
+pub use mutators::registry::{MutatorError, MutatorRegistry};
@@ -8,6 +8,8 @@ mod frac_render;
 mod mutators;
 mod usecase;
 mod modnar;
+mod parallel_chaos_game;
+mod tone_mapping;
 
 use std::time::Instant;
 
@@ -17,7 +19,7 @@ use statistics::grid_density::DensityEstimator2D;
 use chaos_game::ChaosGame;
 
 use crate::frac_render::RgbRenderer;
-use crate::mutators::{MutatorConfig, Mutators};
+use crate::mutators::MutatorConfig;
 use std::env;
 use std::process::exit;
 use crate::alg::combinations::Combinations;
@@ -46,7 +48,9 @@ fn main() {
     //let mut combinations = Combinations::new();
     //let rank = combinations.rank(&vec![8,14,32,36], 48, 4);
     //println!("{rank}");
-    //HDRender::render(4, 144708, None, &presets, None, RenderIter::Large, false, 1024, 1024);
+    //let genome = HDRender::genome(4, 144708, &presets, &[], RenderIter::Large, None, 1024, 1024);
+    //GenomeRepository::save(&genome, "C:\\sizzling_hd_renders\\4.144708.json").unwrap();
+    //HDRender::render(&genome).save("C:\\sizzling_hd_renders\\4.144708.png").unwrap();
     /*let mut starship = StarshipEnterprise::new(&presets, &mut_desc);
     starship.roll_dice_presets("", 500);
     */
@@ -57,10 +61,10 @@ fn main() {
     let sample = chaos_game.run_chaos_game(
         &ifs,
         Some(&[
-            MutatorConfig::new(0.25, Mutators::Disc),
-            MutatorConfig::new(0.25, Mutators::Bent),
-            MutatorConfig::new(0.25, Mutators::Julian { power: 5.0, dist: 0.31 }),
-            MutatorConfig::new(0.25, Mutators::RadianBlur { angle: 1.27, v36: -5.5 })
+            MutatorConfig::named(0.25, "disc", &[]).unwrap(),
+            MutatorConfig::named(0.25, "bent", &[]).unwrap(),
+            MutatorConfig::named(0.25, "julian", &[5.0, 0.31]).unwrap(),
+            MutatorConfig::named(0.25, "radian_blur", &[1.27, -5.5]).unwrap()
         ]),
         200_000
     );
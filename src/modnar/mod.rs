@@ -3,6 +3,7 @@ use crate::util::Len;
 
 pub(crate) mod rnd_vec;
 
+#[derive(Clone)]
 pub struct Modnar {
     seed: u64,
     generator: fn(&mut Self) -> u64
@@ -26,12 +27,17 @@ impl Modnar {
         Self { seed: random_seed_(), generator: rng_}
     }
 
+    /// Same generator as `new_rng`, but reproducible.
+    pub fn new_rng_seeded(seed: u64) -> Self {
+        Self { seed, generator: rng_ }
+    }
+
     pub(crate) fn gen(&mut self, range: RangeInclusive<u64>) -> u64 {
         let val = self.gen_f64();
         range.start().wrapping_add((range.len() as f64 * val).round() as u64)
     }
 
-    pub(crate) fn gen_f32(&mut self) -> f32 {
+    pub fn gen_f32(&mut self) -> f32 {
         self.gen_f64() as f32
     }
 
@@ -200,6 +206,16 @@ mod tests {
         assert!(counter_eq < 10);
     }
 
+    #[test]
+    fn test_rng_seeded() {
+        let mut rng1 = Modnar::new_rng_seeded(42);
+        let mut rng2 = Modnar::new_rng_seeded(42);
+
+        for _ in 0..100 {
+            assert_eq!(rng1.gen_f32(), rng2.gen_f32());
+        }
+    }
+
     #[test]
     fn test_rng_f32() {
         let mut rng1 = Modnar::new_rng();
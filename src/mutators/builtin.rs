@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use crate::ds::affine_mat::AffineMat;
+use crate::ds::point::Point;
+use crate::ff_repository::mutator_description_service::{MutatorDescription, MutatorParam};
+use crate::modnar::Modnar;
+use crate::mutators::Mutator;
+use crate::mutators::mutator_lib::{linear, sinus, spherical, swirl, horseshoe, polar, handkerchief, heart, disc, spiral, hyperbolic, diamond, ex, julia, bent, waves, fisheye, popcorn, exponential, power, cosine, rings, fan, blob, pdj, fan2, rings2, eyefish, bubble, cylinder, perspective, noise, julian, julias, blur, gaussian, radian_blur, pie, ngon, curl, rectangles, arch, tangent, square, rays, blade, secant, twintrian, cross, disc2, super_shape, flower, conic, parabola, bent2, bipolar, boarders, butterfly, cell, cpow, curve, edisc, elliptic, escher, foci, lazysusan, loonie, pre_blur, modulus, oscilloscope, polar2, popcorn2, scry, separation, split, splits, stripes, wedge, wedge_julia, wedge_sph, whorl, waves2, cexp, clog, csin, ccos, ctan, csec, ccsc, ccot, csinh, ccosh, ctanh, csech, ccsch, ccoth, auger, flux, mobius};
+
+/// Mutator of `mutator_lib`, params are passed in the order of the schema.
+pub(crate) struct BuiltinMutator {
+    id: u8,
+    name: &'static str,
+    is_rand: bool,
+    requires_affine: bool,
+    param_names: &'static [&'static str],
+    params: Vec<MutatorParam>,
+    f: fn(&Point, &AffineMat, &mut Modnar, &[f32]) -> Point
+}
+
+impl BuiltinMutator {
+    // params get their bounds and defaults in `described`.
+    fn new(
+        id: u8,
+        name: &'static str,
+        is_rand: bool,
+        requires_affine: bool,
+        param_names: &'static [&'static str],
+        f: fn(&Point, &AffineMat, &mut Modnar, &[f32]) -> Point
+    ) -> Self {
+        Self { id, name, is_rand, requires_affine, param_names, params: Vec::new(), f }
+    }
+
+    // bounds and defaults of the params, from the description w. the same id.
+    fn described(mut self, descriptions: &HashMap<String, MutatorDescription>) -> Self {
+        let desc = descriptions.values().find(|desc| desc.enum_id == self.id);
+
+        self.params = self.param_names
+            .iter()
+            .map(|name| {
+                desc.and_then(|desc| desc.params.iter().flatten().find(|param| param.name == *name))
+                    .cloned()
+                    .unwrap_or_else(|| panic!("No description of param {name} of mutator {}", self.name))
+            })
+            .collect();
+        self
+    }
+}
+
+impl std::fmt::Debug for BuiltinMutator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Mutator for BuiltinMutator {
+    fn id(&self) -> u8 { self.id }
+
+    fn name(&self) -> &str { self.name }
+
+    fn params(&self) -> &[MutatorParam] { &self.params }
+
+    fn is_rand(&self) -> bool { self.is_rand }
+
+    fn requires_affine(&self) -> bool { self.requires_affine }
+
+    #[inline(always)]
+    fn apply(&self, p: &Point, mat: &AffineMat, rnd: &mut Modnar, params: &[f32]) -> Point {
+        (self.f)(p, mat, rnd, params)
+    }
+}
+
+/// All of `mutator_lib`. Ids are flam3 variation numbers (see `flam3::FLAM3_VARIATIONS`), and must stay so:
+/// genomes and mutator descriptions refer to mutators by them.
+/// Bounds and defaults of the params are the ones searched over, they come from `mut_desc.json` (compiled in).
+pub(super) fn builtins() -> Vec<BuiltinMutator> {
+    let descriptions: HashMap<String, MutatorDescription> = serde_json::from_str(include_str!("../mut_desc.json"))
+        .expect("mut_desc.json cannot be parsed!");
+
+    let builtins = vec![
+        BuiltinMutator::new(0, "linear", false, false, &[],
+            |p, _, _, _| linear(p)),
+        BuiltinMutator::new(1, "sinus", false, false, &[],
+            |p, _, _, _| sinus(p)),
+        BuiltinMutator::new(2, "spherical", false, false, &[],
+            |p, _, _, _| spherical(p)),
+        BuiltinMutator::new(3, "swirl", false, false, &[],
+            |p, _, _, _| swirl(p)),
+        BuiltinMutator::new(4, "horseshoe", false, false, &[],
+            |p, _, _, _| horseshoe(p)),
+        BuiltinMutator::new(5, "polar", false, false, &[],
+            |p, _, _, _| polar(p)),
+        BuiltinMutator::new(6, "handkerchief", false, false, &[],
+            |p, _, _, _| handkerchief(p)),
+        BuiltinMutator::new(7, "heart", false, false, &[],
+            |p, _, _, _| heart(p)),
+        BuiltinMutator::new(8, "disc", false, false, &[],
+            |p, _, _, _| disc(p)),
+        BuiltinMutator::new(9, "spiral", false, false, &[],
+            |p, _, _, _| spiral(p)),
+        BuiltinMutator::new(10, "hyperbolic", false, false, &[],
+            |p, _, _, _| hyperbolic(p)),
+        BuiltinMutator::new(11, "diamond", false, false, &[],
+            |p, _, _, _| diamond(p)),
+        BuiltinMutator::new(12, "ex", false, false, &[],
+            |p, _, _, _| ex(p)),
+        BuiltinMutator::new(13, "julia", true, false, &[],
+            |p, _, rnd, _| julia(p, rnd)),
+        BuiltinMutator::new(14, "bent", false, false, &[],
+            |p, _, _, _| bent(p)),
+        BuiltinMutator::new(15, "waves", false, true, &[],
+            |p, mat, _, _| waves(p, mat.b, mat.c, mat.e, mat.f)),
+        BuiltinMutator::new(16, "fisheye", false, false, &[],
+            |p, _, _, _| fisheye(p)),
+        BuiltinMutator::new(17, "popcorn", false, true, &[],
+            |p, mat, _, _| popcorn(p, mat.c, mat.f)),
+        BuiltinMutator::new(18, "exponential", false, false, &[],
+            |p, _, _, _| exponential(p)),
+        BuiltinMutator::new(19, "power", false, false, &[],
+            |p, _, _, _| power(p)),
+        BuiltinMutator::new(20, "cosine", false, false, &[],
+            |p, _, _, _| cosine(p)),
+        BuiltinMutator::new(21, "rings", false, true, &[],
+            |p, mat, _, _| rings(p, mat.c)),
+        BuiltinMutator::new(22, "fan", false, true, &[],
+            |p, mat, _, _| fan(p, mat.c, mat.f)),
+        BuiltinMutator::new(23, "blob", false, false, &["blob_h", "blob_l", "blob_waves"],
+            |p, _, _, v| blob(p, v[0], v[1], v[2])),
+        BuiltinMutator::new(24, "pdj", false, false, &["pdj_a", "pdj_b", "pdj_c", "pdj_d"],
+            |p, _, _, v| pdj(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(25, "fan2", false, false, &["fx", "fy"],
+            |p, _, _, v| fan2(p, v[0], v[1])),
+        BuiltinMutator::new(26, "rings2", false, false, &["rings2_val"],
+            |p, _, _, v| rings2(p, v[0])),
+        BuiltinMutator::new(27, "eyefish", false, false, &[],
+            |p, _, _, _| eyefish(p)),
+        BuiltinMutator::new(28, "bubble", false, false, &[],
+            |p, _, _, _| bubble(p)),
+        BuiltinMutator::new(29, "cylinder", false, false, &[],
+            |p, _, _, _| cylinder(p)),
+        BuiltinMutator::new(30, "perspective", false, false, &["p1_angle", "p2_dist"],
+            |p, _, _, v| perspective(p, v[0], v[1])),
+        BuiltinMutator::new(31, "noise", true, false, &[],
+            |p, _, rnd, _| noise(p, rnd)),
+        BuiltinMutator::new(32, "julian", true, false, &["power", "dist"],
+            |p, _, rnd, v| julian(p, rnd, v[0], v[1])),
+        BuiltinMutator::new(33, "julias", true, false, &["power", "dist"],
+            |p, _, rnd, v| julias(p, rnd, v[0], v[1])),
+        BuiltinMutator::new(34, "blur", true, false, &[],
+            |p, _, rnd, _| blur(p, rnd)),
+        BuiltinMutator::new(35, "gaussian", true, false, &[],
+            |p, _, rnd, _| gaussian(p, rnd)),
+        BuiltinMutator::new(36, "radian_blur", true, false, &["angle", "v36"],
+            |p, _, rnd, v| radian_blur(p, rnd, v[0], v[1])),
+        BuiltinMutator::new(37, "pie", true, false, &["slices", "rotation", "thickness"],
+            |p, _, rnd, v| pie(p, rnd, v[0], v[1], v[2])),
+        BuiltinMutator::new(38, "ngon", false, false, &["power", "sides", "corners", "circle"],
+            |p, _, _, v| ngon(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(39, "curl", false, false, &["c1", "c2"],
+            |p, _, _, v| curl(p, v[0], v[1])),
+        BuiltinMutator::new(40, "rectangles", false, false, &["rect_x", "rect_y"],
+            |p, _, _, v| rectangles(p, v[0], v[1])),
+        BuiltinMutator::new(41, "arch", true, false, &["v41"],
+            |p, _, rnd, v| arch(p, rnd, v[0])),
+        BuiltinMutator::new(42, "tangent", false, false, &[],
+            |p, _, _, _| tangent(p)),
+        BuiltinMutator::new(43, "square", true, false, &[],
+            |p, _, rnd, _| square(p, rnd)),
+        BuiltinMutator::new(44, "rays", true, false, &["v44"],
+            |p, _, rnd, v| rays(p, rnd, v[0])),
+        BuiltinMutator::new(45, "blade", true, false, &["v45"],
+            |p, _, rnd, v| blade(p, rnd, v[0])),
+        BuiltinMutator::new(46, "secant", false, false, &["v46"],
+            |p, _, _, v| secant(p, v[0])),
+        BuiltinMutator::new(47, "twintrian", true, false, &["v47"],
+            |p, _, rnd, v| twintrian(p, rnd, v[0])),
+        BuiltinMutator::new(48, "cross", false, false, &[],
+            |p, _, _, _| cross(p)),
+        BuiltinMutator::new(49, "disc2", false, false, &["rot", "twist"],
+            |p, _, _, v| disc2(p, v[0], v[1])),
+        BuiltinMutator::new(50, "super_shape", true, false, &["m", "n1", "n2", "n3", "randomness", "holes"],
+            |p, _, rnd, v| super_shape(p, rnd, v[0], v[1], v[2], v[3], v[4], v[5])),
+        BuiltinMutator::new(51, "flower", true, false, &["petals", "holes"],
+            |p, _, rnd, v| flower(p, rnd, v[0], v[1])),
+        BuiltinMutator::new(52, "conic", true, false, &["eccentricity", "holes"],
+            |p, _, rnd, v| conic(p, rnd, v[0], v[1])),
+        BuiltinMutator::new(53, "parabola", true, false, &["height", "width"],
+            |p, _, rnd, v| parabola(p, rnd, v[0], v[1])),
+        BuiltinMutator::new(54, "bent2", false, false, &["x", "y"],
+            |p, _, _, v| bent2(p, v[0], v[1])),
+        BuiltinMutator::new(55, "bipolar", false, false, &["shift"],
+            |p, _, _, v| bipolar(p, v[0])),
+        BuiltinMutator::new(56, "boarders", true, false, &[],
+            |p, _, rnd, _| boarders(p, rnd)),
+        BuiltinMutator::new(57, "butterfly", false, false, &[],
+            |p, _, _, _| butterfly(p)),
+        BuiltinMutator::new(58, "cell", false, false, &["size"],
+            |p, _, _, v| cell(p, v[0])),
+        BuiltinMutator::new(59, "cpow", true, false, &["re", "im", "power"],
+            |p, _, rnd, v| cpow(p, rnd, v[0], v[1], v[2])),
+        BuiltinMutator::new(60, "curve", false, false, &["xamp", "yamp", "xlength", "ylength"],
+            |p, _, _, v| curve(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(61, "edisc", false, false, &[],
+            |p, _, _, _| edisc(p)),
+        BuiltinMutator::new(62, "elliptic", false, false, &[],
+            |p, _, _, _| elliptic(p)),
+        BuiltinMutator::new(63, "escher", false, false, &["beta"],
+            |p, _, _, v| escher(p, v[0])),
+        BuiltinMutator::new(64, "foci", false, false, &[],
+            |p, _, _, _| foci(p)),
+        BuiltinMutator::new(65, "lazysusan", false, false, &["spin", "space", "twist", "x", "y", "v65"],
+            |p, _, _, v| lazysusan(p, v[0], v[1], v[2], v[3], v[4], v[5])),
+        BuiltinMutator::new(66, "loonie", false, false, &["v66"],
+            |p, _, _, v| loonie(p, v[0])),
+        BuiltinMutator::new(67, "pre_blur", true, false, &[],
+            |p, _, rnd, _| pre_blur(p, rnd)),
+        BuiltinMutator::new(68, "modulus", false, false, &["x", "y"],
+            |p, _, _, v| modulus(p, v[0], v[1])),
+        BuiltinMutator::new(69, "oscilloscope", false, false, &["separation", "frequency", "amplitude", "damping"],
+            |p, _, _, v| oscilloscope(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(70, "polar2", false, false, &[],
+            |p, _, _, _| polar2(p)),
+        BuiltinMutator::new(71, "popcorn2", false, false, &["x", "y", "c"],
+            |p, _, _, v| popcorn2(p, v[0], v[1], v[2])),
+        BuiltinMutator::new(72, "scry", false, false, &["v72"],
+            |p, _, _, v| scry(p, v[0])),
+        BuiltinMutator::new(73, "separation", false, false, &["x", "xinside", "y", "yinside"],
+            |p, _, _, v| separation(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(74, "split", false, false, &["xsize", "ysize"],
+            |p, _, _, v| split(p, v[0], v[1])),
+        BuiltinMutator::new(75, "splits", false, false, &["x", "y"],
+            |p, _, _, v| splits(p, v[0], v[1])),
+        BuiltinMutator::new(76, "stripes", false, false, &["space", "warp"],
+            |p, _, _, v| stripes(p, v[0], v[1])),
+        BuiltinMutator::new(77, "wedge", false, false, &["angle", "hole", "count", "swirl"],
+            |p, _, _, v| wedge(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(78, "wedge_julia", true, false, &["angle", "count", "power", "dist"],
+            |p, _, rnd, v| wedge_julia(p, rnd, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(79, "wedge_sph", false, false, &["angle", "count", "hole", "swirl"],
+            |p, _, _, v| wedge_sph(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(80, "whorl", false, false, &["inside", "outside", "v80"],
+            |p, _, _, v| whorl(p, v[0], v[1], v[2])),
+        BuiltinMutator::new(81, "waves2", false, false, &["freqx", "scalex", "freqy", "scaley"],
+            |p, _, _, v| waves2(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(82, "exp", false, false, &[],
+            |p, _, _, _| cexp(p)),
+        BuiltinMutator::new(83, "log", false, false, &[],
+            |p, _, _, _| clog(p)),
+        BuiltinMutator::new(84, "sin", false, false, &[],
+            |p, _, _, _| csin(p)),
+        BuiltinMutator::new(85, "cos", false, false, &[],
+            |p, _, _, _| ccos(p)),
+        BuiltinMutator::new(86, "tan", false, false, &[],
+            |p, _, _, _| ctan(p)),
+        BuiltinMutator::new(87, "sec", false, false, &[],
+            |p, _, _, _| csec(p)),
+        BuiltinMutator::new(88, "csc", false, false, &[],
+            |p, _, _, _| ccsc(p)),
+        BuiltinMutator::new(89, "cot", false, false, &[],
+            |p, _, _, _| ccot(p)),
+        BuiltinMutator::new(90, "sinh", false, false, &[],
+            |p, _, _, _| csinh(p)),
+        BuiltinMutator::new(91, "cosh", false, false, &[],
+            |p, _, _, _| ccosh(p)),
+        BuiltinMutator::new(92, "tanh", false, false, &[],
+            |p, _, _, _| ctanh(p)),
+        BuiltinMutator::new(93, "sech", false, false, &[],
+            |p, _, _, _| csech(p)),
+        BuiltinMutator::new(94, "csch", false, false, &[],
+            |p, _, _, _| ccsch(p)),
+        BuiltinMutator::new(95, "coth", false, false, &[],
+            |p, _, _, _| ccoth(p)),
+        BuiltinMutator::new(96, "auger", false, false, &["freq", "auger_weight", "sym", "scale"],
+            |p, _, _, v| auger(p, v[0], v[1], v[2], v[3])),
+        BuiltinMutator::new(97, "flux", false, false, &["spread", "v97"],
+            |p, _, _, v| flux(p, v[0], v[1])),
+        BuiltinMutator::new(98, "mobius", false, false, &["re_a", "im_a", "re_b", "im_b", "re_c", "im_c", "re_d", "im_d"],
+            |p, _, _, v| mobius(p, v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7])),
+    ];
+
+    builtins
+        .into_iter()
+        .map(|mutator| mutator.described(&descriptions))
+        .collect()
+}
@@ -0,0 +1,636 @@
+use std::error::Error;
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use crate::ds::affine_mat::AffineMat;
+use crate::ds::point::Point;
+use crate::ff_repository::mutator_description_service::MutatorParam;
+use crate::modnar::Modnar;
+use crate::mutators::Mutator;
+
+/// Mutator, defined at runtime: x' and y' as expressions of the point.
+///
+/// Variables: `x`, `y`, `r` (distance to the origin), `r2` (its square), `theta` (`atan2(x, y)`), `phi` (`atan2(y, x)`),
+/// `a`..`f` (coefficients of the transform's affine, see `AffineMat`), `pi`, and the params by name.
+/// Random: `psi` (uniform in [0..1)), `omega` (0 or pi), `lambda` (-1 or 1) are drawn once per application,
+/// both expressions see the same draw. `rand()` is drawn anew on each use.
+/// Operators: `+ - * / % ^` (`^` - power, right associative), `< <= > >=` (1 or 0), unary `-`.
+/// Functions: `sin cos tan asin acos atan sinh cosh tanh exp ln sqrt abs floor ceil round trunc sign` of one argument,
+/// `atan2 pow min max mod` of two, `if(cond, then, else)` - `then` if `cond` > 0.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExpressionSource {
+    pub x: String,
+    pub y: String
+}
+
+impl ExpressionSource {
+    pub fn new(x: &str, y: &str) -> Self {
+        Self { x: x.to_string(), y: y.to_string() }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExpressionError {
+    /// Byte offset in the expression.
+    pub position: usize,
+    pub message: String
+}
+
+impl ExpressionError {
+    fn new(position: usize, message: String) -> Self {
+        Self { position, message }
+    }
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message, self.position)
+    }
+}
+
+impl Error for ExpressionError {}
+
+/// Compiled `ExpressionSource`.
+#[derive(Debug)]
+pub(crate) struct ExpressionMutator {
+    id: u8,
+    name: String,
+    source: ExpressionSource,
+    params: Vec<MutatorParam>,
+    x: Program,
+    y: Program,
+    // by `Var`.
+    uses: [bool; VAR_COUNT]
+}
+
+impl ExpressionMutator {
+    /// `params` - in the order, the expression params are given values in.
+    pub(crate) fn compile(id: u8, name: &str, source: ExpressionSource, params: Vec<MutatorParam>) -> Result<Self, ExpressionError> {
+        let param_names: Vec<String> = params.iter().map(|param| param.name.clone()).collect();
+        if let Some(name) = param_names.iter().find(|name| Self::is_reserved(name)) {
+            return Err(ExpressionError::new(0, format!("param {name} shadows a variable or function")));
+        }
+
+        fn prefixed(expr: &'static str) -> impl Fn(ExpressionError) -> ExpressionError {
+            move |error| ExpressionError::new(error.position, format!("{expr}: {}", error.message))
+        }
+        let x = Program::compile(&source.x, &param_names).map_err(prefixed("x"))?;
+        let y = Program::compile(&source.y, &param_names).map_err(prefixed("y"))?;
+
+        let mut uses = [false; VAR_COUNT];
+        x.ops.iter().chain(y.ops.iter()).for_each(|op| if let Op::Var(var) = op { uses[*var as usize] = true; });
+
+        Ok(Self { id, name: name.to_string(), source, params, x, y, uses })
+    }
+
+    fn is_reserved(name: &str) -> bool {
+        Var::by(name).is_some() || name == "pi" || name == "rand" || name == "if"
+            || Fn1::by(name).is_some() || BinOp::by_fn(name).is_some()
+    }
+}
+
+impl Mutator for ExpressionMutator {
+    fn id(&self) -> u8 { self.id }
+
+    fn name(&self) -> &str { &self.name }
+
+    fn params(&self) -> &[MutatorParam] { &self.params }
+
+    fn is_rand(&self) -> bool {
+        self.uses[Var::Psi as usize] || self.uses[Var::Omega as usize] || self.uses[Var::Lambda as usize]
+            || self.x.ops.iter().chain(self.y.ops.iter()).any(|op| matches!(op, Op::Rand))
+    }
+
+    fn requires_affine(&self) -> bool {
+        [Var::A, Var::B, Var::C, Var::D, Var::E, Var::F].iter().any(|var| self.uses[*var as usize])
+    }
+
+    fn apply(&self, p: &Point, mat: &AffineMat, rnd: &mut Modnar, params: &[f32]) -> Point {
+        let mut vars = [0.0f32; VAR_COUNT];
+        let uses = |var: Var| self.uses[var as usize];
+
+        vars[Var::X as usize] = p.x;
+        vars[Var::Y as usize] = p.y;
+        vars[Var::R2 as usize] = p.x*p.x + p.y*p.y;
+        if uses(Var::R) { vars[Var::R as usize] = vars[Var::R2 as usize].sqrt(); }
+        if uses(Var::Theta) { vars[Var::Theta as usize] = p.x.atan2(p.y); }
+        if uses(Var::Phi) { vars[Var::Phi as usize] = p.y.atan2(p.x); }
+
+        vars[Var::A as usize] = mat.a;
+        vars[Var::B as usize] = mat.b;
+        vars[Var::C as usize] = mat.c;
+        vars[Var::D as usize] = mat.d;
+        vars[Var::E as usize] = mat.e;
+        vars[Var::F as usize] = mat.f;
+
+        // drawn only when used, so that the other mutators see the same random sequence.
+        if uses(Var::Psi) { vars[Var::Psi as usize] = rnd.gen_f32(); }
+        if uses(Var::Omega) { vars[Var::Omega as usize] = if rnd.gen_f32() > 0.5 { std::f32::consts::PI } else { 0.0 }; }
+        if uses(Var::Lambda) { vars[Var::Lambda as usize] = if rnd.gen_f32() < 0.5 { -1.0 } else { 1.0 }; }
+
+        Point::new(
+            self.x.eval(&vars, params, rnd),
+            self.y.eval(&vars, params, rnd)
+        )
+    }
+
+
+    fn expression(&self) -> Option<&ExpressionSource> { Some(&self.source) }
+}
+
+const VAR_COUNT: usize = 15;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Var { X, Y, R, R2, Theta, Phi, A, B, C, D, E, F, Psi, Omega, Lambda }
+
+impl Var {
+    fn by(name: &str) -> Option<Self> {
+        let var = match name {
+            "x" => Var::X, "y" => Var::Y, "r" => Var::R, "r2" => Var::R2, "theta" => Var::Theta, "phi" => Var::Phi,
+            "a" => Var::A, "b" => Var::B, "c" => Var::C, "d" => Var::D, "e" => Var::E, "f" => Var::F,
+            "psi" => Var::Psi, "omega" => Var::Omega, "lambda" => Var::Lambda,
+            _ => return None
+        };
+        Some(var)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Fn1 { Neg, Sin, Cos, Tan, Asin, Acos, Atan, Sinh, Cosh, Tanh, Exp, Ln, Sqrt, Abs, Floor, Ceil, Round, Trunc, Sign }
+
+impl Fn1 {
+    fn by(name: &str) -> Option<Self> {
+        let f = match name {
+            "sin" => Fn1::Sin, "cos" => Fn1::Cos, "tan" => Fn1::Tan, "asin" => Fn1::Asin, "acos" => Fn1::Acos,
+            "atan" => Fn1::Atan, "sinh" => Fn1::Sinh, "cosh" => Fn1::Cosh, "tanh" => Fn1::Tanh, "exp" => Fn1::Exp,
+            "ln" => Fn1::Ln, "sqrt" => Fn1::Sqrt, "abs" => Fn1::Abs, "floor" => Fn1::Floor, "ceil" => Fn1::Ceil,
+            "round" => Fn1::Round, "trunc" => Fn1::Trunc, "sign" => Fn1::Sign,
+            _ => return None
+        };
+        Some(f)
+    }
+
+    fn eval(self, v: f32) -> f32 {
+        match self {
+            Fn1::Neg => -v,
+            Fn1::Sin => v.sin(),
+            Fn1::Cos => v.cos(),
+            Fn1::Tan => v.tan(),
+            Fn1::Asin => v.asin(),
+            Fn1::Acos => v.acos(),
+            Fn1::Atan => v.atan(),
+            Fn1::Sinh => v.sinh(),
+            Fn1::Cosh => v.cosh(),
+            Fn1::Tanh => v.tanh(),
+            Fn1::Exp => v.exp(),
+            Fn1::Ln => v.ln(),
+            Fn1::Sqrt => v.sqrt(),
+            Fn1::Abs => v.abs(),
+            Fn1::Floor => v.floor(),
+            Fn1::Ceil => v.ceil(),
+            Fn1::Round => v.round(),
+            Fn1::Trunc => v.trunc(),
+            Fn1::Sign => if v > 0.0 { 1.0 } else if v < 0.0 { -1.0 } else { 0.0 }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum BinOp { Add, Sub, Mul, Div, Mod, Pow, Lt, Le, Gt, Ge, Atan2, Min, Max }
+
+impl BinOp {
+    fn by_fn(name: &str) -> Option<Self> {
+        let op = match name {
+            "atan2" => BinOp::Atan2, "pow" => BinOp::Pow, "min" => BinOp::Min, "max" => BinOp::Max, "mod" => BinOp::Mod,
+            _ => return None
+        };
+        Some(op)
+    }
+
+    fn eval(self, lhs: f32, rhs: f32) -> f32 {
+        let truth = |cond: bool| if cond { 1.0 } else { 0.0 };
+        match self {
+            BinOp::Add => lhs + rhs,
+            BinOp::Sub => lhs - rhs,
+            BinOp::Mul => lhs * rhs,
+            BinOp::Div => lhs / rhs,
+            BinOp::Mod => lhs % rhs,
+            BinOp::Pow => lhs.powf(rhs),
+            BinOp::Lt => truth(lhs < rhs),
+            BinOp::Le => truth(lhs <= rhs),
+            BinOp::Gt => truth(lhs > rhs),
+            BinOp::Ge => truth(lhs >= rhs),
+            BinOp::Atan2 => lhs.atan2(rhs),
+            BinOp::Min => lhs.min(rhs),
+            BinOp::Max => lhs.max(rhs)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Const(f32),
+    Var(Var),
+    Param(usize),
+    Rand,
+    Fn1(Fn1, Box<Node>),
+    BinOp(BinOp, Box<Node>, Box<Node>),
+    If(Box<Node>, Box<Node>, Box<Node>)
+}
+
+impl Node {
+    // constant subtrees are evaluated once, here.
+    fn fold(self) -> Node {
+        match self {
+            Node::Fn1(f, arg) => match arg.fold() {
+                Node::Const(v) => Node::Const(f.eval(v)),
+                arg => Node::Fn1(f, Box::new(arg))
+            },
+            Node::BinOp(op, lhs, rhs) => match (lhs.fold(), rhs.fold()) {
+                (Node::Const(lhs), Node::Const(rhs)) => Node::Const(op.eval(lhs, rhs)),
+                (lhs, rhs) => Node::BinOp(op, Box::new(lhs), Box::new(rhs))
+            },
+            Node::If(cond, then, other) => match cond.fold() {
+                Node::Const(cond) => if cond > 0.0 { then.fold() } else { other.fold() },
+                cond => Node::If(Box::new(cond), Box::new(then.fold()), Box::new(other.fold()))
+            },
+            node => node
+        }
+    }
+}
+
+// stack machine, operands in postfix order.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Op {
+    Const(f32),
+    Var(Var),
+    Param(usize),
+    Rand,
+    Fn1(Fn1),
+    BinOp(BinOp),
+    // cond, then, else -> one of them.
+    Select
+}
+
+#[derive(Debug)]
+struct Program {
+    ops: Vec<Op>
+}
+
+impl Program {
+    const MAX_DEPTH: usize = 32;
+
+    fn compile(src: &str, param_names: &[String]) -> Result<Self, ExpressionError> {
+        let tokens = Lexer::tokenize(src)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0, param_names, end: src.len() };
+        let node = parser.expr()?;
+        if let Some(token) = parser.peek() {
+            return Err(ExpressionError::new(token.position, format!("unexpected {:?}", token.kind)));
+        }
+
+        let mut ops = Vec::new();
+        Self::emit(&node.fold(), &mut ops);
+
+        let mut depth = 0usize;
+        let mut max_depth = 0usize;
+        for op in ops.iter() {
+            depth = match op {
+                Op::Const(_) | Op::Var(_) | Op::Param(_) | Op::Rand => depth + 1,
+                Op::Fn1(_) => depth,
+                Op::BinOp(_) => depth - 1,
+                Op::Select => depth - 2
+            };
+            max_depth = max_depth.max(depth);
+        }
+        if max_depth > Self::MAX_DEPTH {
+            return Err(ExpressionError::new(0, format!("nested deeper than {}", Self::MAX_DEPTH)));
+        }
+
+        Ok(Self { ops })
+    }
+
+    fn emit(node: &Node, ops: &mut Vec<Op>) {
+        match node {
+            Node::Const(v) => ops.push(Op::Const(*v)),
+            Node::Var(var) => ops.push(Op::Var(*var)),
+            Node::Param(i) => ops.push(Op::Param(*i)),
+            Node::Rand => ops.push(Op::Rand),
+            Node::Fn1(f, arg) => {
+                Self::emit(arg, ops);
+                ops.push(Op::Fn1(*f));
+            }
+            Node::BinOp(op, lhs, rhs) => {
+                Self::emit(lhs, ops);
+                Self::emit(rhs, ops);
+                ops.push(Op::BinOp(*op));
+            }
+            Node::If(cond, then, other) => {
+                Self::emit(cond, ops);
+                Self::emit(then, ops);
+                Self::emit(other, ops);
+                ops.push(Op::Select);
+            }
+        }
+    }
+
+    fn eval(&self, vars: &[f32; VAR_COUNT], params: &[f32], rnd: &mut Modnar) -> f32 {
+        let mut stack = [0.0f32; Self::MAX_DEPTH];
+        let mut top = 0usize;
+
+        for op in self.ops.iter() {
+            match *op {
+                Op::Const(v) => { stack[top] = v; top += 1; }
+                Op::Var(var) => { stack[top] = vars[var as usize]; top += 1; }
+                Op::Param(i) => { stack[top] = params[i]; top += 1; }
+                Op::Rand => { stack[top] = rnd.gen_f32(); top += 1; }
+                Op::Fn1(f) => stack[top - 1] = f.eval(stack[top - 1]),
+                Op::BinOp(op) => {
+                    top -= 1;
+                    stack[top - 1] = op.eval(stack[top - 1], stack[top]);
+                }
+                Op::Select => {
+                    top -= 2;
+                    stack[top - 1] = if stack[top - 1] > 0.0 { stack[top] } else { stack[top + 1] };
+                }
+            }
+        }
+
+        stack[0]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind { Num(f32), Ident(String), Sym(&'static str) }
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    position: usize
+}
+
+struct Lexer;
+
+impl Lexer {
+    const SYMBOLS: [&'static str; 13] = ["<=", ">=", "<", ">", "+", "-", "*", "/", "%", "^", "(", ")", ","];
+
+    fn tokenize(src: &str) -> Result<Vec<Token>, ExpressionError> {
+        let bytes = src.as_bytes();
+        let mut tokens = Vec::new();
+        let mut pos = 0;
+
+        while pos < bytes.len() {
+            let ch = bytes[pos] as char;
+            let start = pos;
+
+            if ch.is_ascii_whitespace() {
+                pos += 1;
+            } else if ch.is_ascii_digit() || (ch == '.' && bytes.get(pos + 1).is_some_and(|b| b.is_ascii_digit())) {
+                while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+                    pos += 1;
+                }
+                // exponent, i.e. 1e-3
+                if pos < bytes.len() && (bytes[pos] == b'e' || bytes[pos] == b'E')
+                    && bytes.get(pos + 1).is_some_and(|b| b.is_ascii_digit() || *b == b'-' || *b == b'+') {
+                    pos += 2;
+                    while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                        pos += 1;
+                    }
+                }
+                let value = src[start..pos]
+                    .parse::<f32>()
+                    .map_err(|_| ExpressionError::new(start, format!("bad number {}", &src[start..pos])))?;
+                tokens.push(Token { kind: TokenKind::Num(value), position: start });
+            } else if ch.is_ascii_alphabetic() || ch == '_' {
+                while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                    pos += 1;
+                }
+                tokens.push(Token { kind: TokenKind::Ident(src[start..pos].to_string()), position: start });
+            } else if let Some(sym) = Self::SYMBOLS.iter().find(|sym| src[pos..].starts_with(**sym)) {
+                pos += sym.len();
+                tokens.push(Token { kind: TokenKind::Sym(sym), position: start });
+            } else {
+                return Err(ExpressionError::new(start, format!("unexpected {ch}")));
+            }
+        }
+
+        Ok(tokens)
+    }
+}
+
+// recursive descent, lowest precedence first: comparison, sum, product, unary minus, power.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    param_names: &'a [String],
+    end: usize
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn position(&self) -> usize {
+        self.peek().map(|token| token.position).unwrap_or(self.end)
+    }
+
+    fn eat(&mut self, sym: &str) -> bool {
+        match self.peek() {
+            Some(Token { kind: TokenKind::Sym(s), .. }) if *s == sym => {
+                self.pos += 1;
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn expect(&mut self, sym: &str) -> Result<(), ExpressionError> {
+        if self.eat(sym) { Ok(()) } else { Err(ExpressionError::new(self.position(), format!("expected {sym}"))) }
+    }
+
+    fn expr(&mut self) -> Result<Node, ExpressionError> {
+        let lhs = self.sum()?;
+        let op = [("<=", BinOp::Le), (">=", BinOp::Ge), ("<", BinOp::Lt), (">", BinOp::Gt)]
+            .into_iter()
+            .find(|(sym, _)| self.eat(sym));
+
+        match op {
+            Some((_, op)) => Ok(Node::BinOp(op, Box::new(lhs), Box::new(self.sum()?))),
+            None => Ok(lhs)
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node, ExpressionError> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = if self.eat("+") { BinOp::Add } else if self.eat("-") { BinOp::Sub } else { return Ok(lhs) };
+            lhs = Node::BinOp(op, Box::new(lhs), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, ExpressionError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                BinOp::Mul
+            } else if self.eat("/") {
+                BinOp::Div
+            } else if self.eat("%") {
+                BinOp::Mod
+            } else {
+                return Ok(lhs)
+            };
+            lhs = Node::BinOp(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    // -x^2 is -(x^2).
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.eat("-") {
+            return Ok(Node::Fn1(Fn1::Neg, Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Node, ExpressionError> {
+        let base = self.primary()?;
+        if self.eat("^") {
+            return Ok(Node::BinOp(BinOp::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Node, ExpressionError> {
+        let position = self.position();
+        let Some(token) = self.peek() else {
+            return Err(ExpressionError::new(position, String::from("unexpected end")));
+        };
+        self.pos += 1;
+
+        match &token.kind {
+            TokenKind::Num(value) => Ok(Node::Const(*value)),
+            TokenKind::Sym("(") => {
+                let node = self.expr()?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            TokenKind::Ident(name) if self.eat("(") => self.call(name, position),
+            TokenKind::Ident(name) => {
+                if let Some(i) = self.param_names.iter().position(|param| param == name) {
+                    Ok(Node::Param(i))
+                } else if let Some(var) = Var::by(name) {
+                    Ok(Node::Var(var))
+                } else if name == "pi" {
+                    Ok(Node::Const(std::f32::consts::PI))
+                } else {
+                    Err(ExpressionError::new(position, format!("unknown variable {name}")))
+                }
+            }
+            kind => Err(ExpressionError::new(position, format!("unexpected {kind:?}")))
+        }
+    }
+
+    fn call(&mut self, name: &str, position: usize) -> Result<Node, ExpressionError> {
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.expr()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+
+        let arity = if name == "rand" { 0 } else if name == "if" { 3 } else if Fn1::by(name).is_some() { 1 } else if BinOp::by_fn(name).is_some() { 2 } else {
+            return Err(ExpressionError::new(position, format!("unknown function {name}")));
+        };
+        if args.len() != arity {
+            return Err(ExpressionError::new(position, format!("{name} takes {arity} arguments, got {}", args.len())));
+        }
+
+        let mut args = args.into_iter().map(Box::new);
+        let mut arg = || args.next().unwrap();
+        let node = match name {
+            "rand" => Node::Rand,
+            "if" => Node::If(arg(), arg(), arg()),
+            _ => match Fn1::by(name) {
+                Some(f) => Node::Fn1(f, arg()),
+                None => Node::BinOp(BinOp::by_fn(name).unwrap(), arg(), arg())
+            }
+        };
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::point::Point;
+    use crate::ff_repository::mutator_description_service::MutatorParam;
+    use crate::modnar::Modnar;
+    use crate::mutators::{Mutator, MutatorConfig};
+    use super::{ExpressionError, ExpressionMutator, ExpressionSource, Op};
+
+    fn compile(x: &str, y: &str, params: &[&str]) -> Result<ExpressionMutator, ExpressionError> {
+        let params = params
+            .iter()
+            .map(|name| MutatorParam { name: name.to_string(), lower_bound: 0.0, upper_bound: 1.0, default_value: 1.0 })
+            .collect();
+        ExpressionMutator::compile(200, "test", ExpressionSource::new(x, y), params)
+    }
+
+    #[test]
+    fn test_eval() {
+        // swirl
+        let swirl = compile("x*sin(r2) - y*cos(r2)", "x*cos(r2) + y*sin(r2)", &[]).unwrap();
+        let p = Point::new(0.3, -0.7);
+        let rsq: f32 = 0.3 * 0.3 + 0.7 * 0.7;
+        let res = swirl.apply(&p, &AffineMat::identity(), &mut Modnar::new_rng_seeded(42), &[]);
+        assert!((res.x - (0.3 * rsq.sin() + 0.7 * rsq.cos())).abs() < 1e-6);
+        assert!((res.y - (0.3 * rsq.cos() - 0.7 * rsq.sin())).abs() < 1e-6);
+        assert!(!swirl.is_rand() && !swirl.requires_affine());
+
+        // precedence, params, comparisons, affine.
+        let misc = compile("-2^2 + k % 3 * 2", "if(x > 0, b, -1) + max(1, 2)", &["k"]).unwrap();
+        let res = misc.apply(&p, &AffineMat::from(1.0, 5.0, 0.0, 1.0, 0.0, 0.0), &mut Modnar::new_rng_seeded(42), &[7.0]);
+        assert_eq!((res.x, res.y), (-2.0, 7.0));
+        assert!(misc.requires_affine());
+
+        // omega is drawn once, so julia stays on the same branch for x and y.
+        let julia = compile("sqrt(r)*cos(phi/2 + omega)", "sqrt(r)*sin(phi/2 + omega)", &[]).unwrap();
+        let res = julia.apply(&Point::new(1.0, 0.0), &AffineMat::identity(), &mut Modnar::new_rng_seeded(42), &[]);
+        assert!(((res.x.abs() - 1.0).abs() < 1e-6) && res.y.abs() < 1e-6);
+        assert!(julia.is_rand());
+    }
+
+    #[test]
+    fn test_folding() {
+        let mutator = compile("2*pi*(1 + 1)", "x + sin(0)", &[]).unwrap();
+        assert_eq!(mutator.x.ops, vec![Op::Const(4.0 * std::f32::consts::PI)]);
+        assert_eq!(mutator.y.ops.len(), 3);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(compile("x + z", "y", &[]).unwrap_err(), ExpressionError { position: 4, message: String::from("x: unknown variable z") });
+        assert_eq!(compile("x", "sin(y", &[]).unwrap_err().position, 5);
+        assert_eq!(compile("x", "atan2(y)", &[]).unwrap_err().message, "y: atan2 takes 2 arguments, got 1");
+        assert!(compile("x", "y y", &[]).is_err());
+        assert!(compile("x", "y", &["r"]).is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        // saved w. the source, registered on load.
+        let json = r#"{"weight": 0.5, "mutator": "expression_serde_test", "params": {"k": 2.0}, "expression": {"x": "x*k", "y": "y"}}"#;
+        let config: MutatorConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.params, vec![2.0]);
+        assert_eq!(config.mutator.expression(), Some(&ExpressionSource::new("x*k", "y")));
+
+        let parsed: MutatorConfig = serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(parsed, config);
+
+        let other = r#"{"weight": 0.5, "mutator": "expression_serde_test", "expression": {"x": "x", "y": "y"}}"#;
+        assert!(serde_json::from_str::<MutatorConfig>(other).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::mutators::MutatorConfig;
+use crate::mutators::registry::MutatorRegistry;
+
+// flam3 variation names, along with the names of their parameters (as xform attributes).
+// Missing parameters get flam3's defaults.
+pub(crate) const FLAM3_VARIATIONS: [&str; 99] = [
+    "linear", "sinusoidal", "spherical", "swirl", "horseshoe", "polar", "handkerchief", "heart", "disc", "spiral",
+    "hyperbolic", "diamond", "ex", "julia", "bent", "waves", "fisheye", "popcorn", "exponential", "power",
+    "cosine", "rings", "fan", "blob", "pdj", "fan2", "rings2", "eyefish", "bubble", "cylinder",
+    "perspective", "noise", "julian", "juliascope", "blur", "gaussian_blur", "radial_blur", "pie", "ngon", "curl",
+    "rectangles", "arch", "tangent", "square", "rays", "blade", "secant2", "twintrian", "cross",
+    "disc2", "super_shape", "flower", "conic", "parabola", "bent2", "bipolar", "boarders", "butterfly", "cell",
+    "cpow", "curve", "edisc", "elliptic", "escher", "foci", "lazysusan", "loonie", "pre_blur", "modulus",
+    "oscilloscope", "polar2", "popcorn2", "scry", "separation", "split", "splits", "stripes", "wedge", "wedge_julia",
+    "wedge_sph", "whorl", "waves2", "exp", "log", "sin", "cos", "tan", "sec", "csc",
+    "cot", "sinh", "cosh", "tanh", "sech", "csch", "coth", "auger", "flux", "mobius"
+];
+
+/// Parameter of a mutator, as flam3 has it.
+enum Flam3Param {
+    /// xform attribute (i.e. "julian_power") and flam3's default.
+    Attr(&'static str, f32),
+    /// the variation weight, flam3 uses it non-linearly (i.e. `v36` of radial blur).
+    Weight
+}
+
+// by mutator id, in the order of the mutator's params. Mutators w/o params aren't here.
+static FLAM3_PARAMS: [(u8, &[Flam3Param]); 46] = [
+    (23, &[Flam3Param::Attr("blob_high", 1.0), Flam3Param::Attr("blob_low", 0.0), Flam3Param::Attr("blob_waves", 1.0)]),
+    (24, &[Flam3Param::Attr("pdj_a", 0.0), Flam3Param::Attr("pdj_b", 0.0), Flam3Param::Attr("pdj_c", 0.0), Flam3Param::Attr("pdj_d", 0.0)]),
+    (25, &[Flam3Param::Attr("fan2_x", 0.0), Flam3Param::Attr("fan2_y", 0.0)]),
+    (26, &[Flam3Param::Attr("rings2_val", 0.0)]),
+    (30, &[Flam3Param::Attr("perspective_angle", 0.0), Flam3Param::Attr("perspective_dist", 0.0)]),
+    (32, &[Flam3Param::Attr("julian_power", 1.0), Flam3Param::Attr("julian_dist", 1.0)]),
+    (33, &[Flam3Param::Attr("juliascope_power", 1.0), Flam3Param::Attr("juliascope_dist", 1.0)]),
+    (36, &[Flam3Param::Attr("radial_blur_angle", 0.0), Flam3Param::Weight]),
+    (37, &[Flam3Param::Attr("pie_slices", 6.0), Flam3Param::Attr("pie_rotation", 0.0), Flam3Param::Attr("pie_thickness", 0.5)]),
+    (38, &[Flam3Param::Attr("ngon_power", 3.0), Flam3Param::Attr("ngon_sides", 5.0), Flam3Param::Attr("ngon_corners", 2.0), Flam3Param::Attr("ngon_circle", 1.0)]),
+    (39, &[Flam3Param::Attr("curl_c1", 0.0), Flam3Param::Attr("curl_c2", 0.0)]),
+    (40, &[Flam3Param::Attr("rectangles_x", 1.0), Flam3Param::Attr("rectangles_y", 1.0)]),
+    (41, &[Flam3Param::Weight]),
+    (44, &[Flam3Param::Weight]),
+    (45, &[Flam3Param::Weight]),
+    (46, &[Flam3Param::Weight]),
+    (47, &[Flam3Param::Weight]),
+    (49, &[Flam3Param::Attr("disc2_rot", 0.0), Flam3Param::Attr("disc2_twist", 0.0)]),
+    (50, &[Flam3Param::Attr("super_shape_m", 0.0), Flam3Param::Attr("super_shape_n1", 1.0), Flam3Param::Attr("super_shape_n2", 1.0), Flam3Param::Attr("super_shape_n3", 1.0), Flam3Param::Attr("super_shape_rnd", 0.0), Flam3Param::Attr("super_shape_holes", 0.0)]),
+    (51, &[Flam3Param::Attr("flower_petals", 0.0), Flam3Param::Attr("flower_holes", 0.0)]),
+    (52, &[Flam3Param::Attr("conic_eccentricity", 1.0), Flam3Param::Attr("conic_holes", 0.0)]),
+    (53, &[Flam3Param::Attr("parabola_height", 0.0), Flam3Param::Attr("parabola_width", 0.0)]),
+    (54, &[Flam3Param::Attr("bent2_x", 1.0), Flam3Param::Attr("bent2_y", 1.0)]),
+    (55, &[Flam3Param::Attr("bipolar_shift", 0.0)]),
+    (58, &[Flam3Param::Attr("cell_size", 1.0)]),
+    (59, &[Flam3Param::Attr("cpow_r", 1.0), Flam3Param::Attr("cpow_i", 0.0), Flam3Param::Attr("cpow_power", 1.0)]),
+    (60, &[Flam3Param::Attr("curve_xamp", 0.0), Flam3Param::Attr("curve_yamp", 0.0), Flam3Param::Attr("curve_xlength", 1.0), Flam3Param::Attr("curve_ylength", 1.0)]),
+    (63, &[Flam3Param::Attr("escher_beta", 0.0)]),
+    (65, &[Flam3Param::Attr("lazysusan_spin", 0.0), Flam3Param::Attr("lazysusan_space", 0.0), Flam3Param::Attr("lazysusan_twist", 0.0), Flam3Param::Attr("lazysusan_x", 0.0), Flam3Param::Attr("lazysusan_y", 0.0), Flam3Param::Weight]),
+    (66, &[Flam3Param::Weight]),
+    (68, &[Flam3Param::Attr("modulus_x", 0.0), Flam3Param::Attr("modulus_y", 0.0)]),
+    (69, &[Flam3Param::Attr("oscilloscope_separation", 1.0), Flam3Param::Attr("oscilloscope_frequency", std::f32::consts::PI), Flam3Param::Attr("oscilloscope_amplitude", 1.0), Flam3Param::Attr("oscilloscope_damping", 0.0)]),
+    (71, &[Flam3Param::Attr("popcorn2_x", 0.0), Flam3Param::Attr("popcorn2_y", 0.0), Flam3Param::Attr("popcorn2_c", 0.0)]),
+    (72, &[Flam3Param::Weight]),
+    (73, &[Flam3Param::Attr("separation_x", 0.0), Flam3Param::Attr("separation_xinside", 0.0), Flam3Param::Attr("separation_y", 0.0), Flam3Param::Attr("separation_yinside", 0.0)]),
+    (74, &[Flam3Param::Attr("split_xsize", 0.0), Flam3Param::Attr("split_ysize", 0.0)]),
+    (75, &[Flam3Param::Attr("splits_x", 0.0), Flam3Param::Attr("splits_y", 0.0)]),
+    (76, &[Flam3Param::Attr("stripes_space", 0.0), Flam3Param::Attr("stripes_warp", 0.0)]),
+    (77, &[Flam3Param::Attr("wedge_angle", 0.0), Flam3Param::Attr("wedge_hole", 0.0), Flam3Param::Attr("wedge_count", 1.0), Flam3Param::Attr("wedge_swirl", 0.0)]),
+    (78, &[Flam3Param::Attr("wedge_julia_angle", 0.0), Flam3Param::Attr("wedge_julia_count", 1.0), Flam3Param::Attr("wedge_julia_power", 1.0), Flam3Param::Attr("wedge_julia_dist", 0.0)]),
+    (79, &[Flam3Param::Attr("wedge_sph_angle", 0.0), Flam3Param::Attr("wedge_sph_count", 1.0), Flam3Param::Attr("wedge_sph_hole", 0.0), Flam3Param::Attr("wedge_sph_swirl", 0.0)]),
+    (80, &[Flam3Param::Attr("whorl_inside", 1.0), Flam3Param::Attr("whorl_outside", 1.0), Flam3Param::Weight]),
+    (81, &[Flam3Param::Attr("waves2_freqx", 0.0), Flam3Param::Attr("waves2_scalex", 0.0), Flam3Param::Attr("waves2_freqy", 0.0), Flam3Param::Attr("waves2_scaley", 0.0)]),
+    (96, &[Flam3Param::Attr("auger_freq", 1.0), Flam3Param::Attr("auger_weight", 0.5), Flam3Param::Attr("auger_sym", 0.0), Flam3Param::Attr("auger_scale", 1.0)]),
+    (97, &[Flam3Param::Attr("flux_spread", 0.0), Flam3Param::Weight]),
+    (98, &[Flam3Param::Attr("mobius_re_a", 1.0), Flam3Param::Attr("mobius_im_a", 0.0), Flam3Param::Attr("mobius_re_b", 0.0), Flam3Param::Attr("mobius_im_b", 0.0), Flam3Param::Attr("mobius_re_c", 0.0), Flam3Param::Attr("mobius_im_c", 0.0), Flam3Param::Attr("mobius_re_d", 1.0), Flam3Param::Attr("mobius_im_d", 0.0)]),
+];
+
+fn flam3_params(id: u8) -> &'static [Flam3Param] {
+    FLAM3_PARAMS
+        .iter()
+        .find(|(param_id, _)| *param_id == id)
+        .map(|(_, params)| *params)
+        .unwrap_or(&[])
+}
+
+/// Mutator for the flam3 variation `name` with weight `weight`, `None` if there is no such mutator.
+/// `param` - looks up a variation parameter (i.e. "julian_power") of the xform.
+///
+/// Parameters like `v36` of radial blur are the variation weight in flam3.
+pub(crate) fn from_flam3<F>(name: &str, weight: f32, param: F) -> Option<MutatorConfig> where F: Fn(&str) -> Option<f32> {
+    let id = FLAM3_VARIATIONS.iter().position(|variation| *variation == name)? as u8;
+    let mutator = MutatorRegistry::global().read().unwrap().get(id)?.clone();
+
+    let params = flam3_params(id)
+        .iter()
+        .map(|flam3_param| match *flam3_param {
+            Flam3Param::Attr(attr, default) => param(attr).unwrap_or(default),
+            Flam3Param::Weight => weight
+        })
+        .collect();
+
+    Some(MutatorConfig::new(weight, mutator, params))
+}
+
+/// flam3 variation name and its parameters (except the ones, which are the weight in flam3).
+/// `None` - no such variation in flam3 (expression and other non built-in mutators).
+pub(crate) fn to_flam3(config: &MutatorConfig) -> Option<(&'static str, Vec<(&'static str, f32)>)> {
+    let id = config.mutator.id();
+    // the built-ins hold the ids of flam3 variations, the registry doesn't hand them out to any other mutator.
+    let name = *FLAM3_VARIATIONS.get(id as usize)?;
+
+    let params = flam3_params(id)
+        .iter()
+        .zip(&config.params)
+        .filter_map(|(flam3_param, value)| match *flam3_param {
+            Flam3Param::Attr(attr, _) => Some((attr, *value)),
+            Flam3Param::Weight => None
+        })
+        .collect();
+
+    Some((name, params))
+}
+
+/// Whether `attr` is a parameter of one of the `FLAM3_VARIATIONS` (i.e. "julian_power").
+/// Variations, named after another one w. a suffix (i.e. "wedge_julia"), aren't.
+pub(crate) fn is_flam3_param(attr: &str) -> bool {
+    !FLAM3_VARIATIONS.contains(&attr) && FLAM3_VARIATIONS
+        .iter()
+        .any(|name| attr.strip_prefix(name).map(|rest| rest.starts_with('_')).unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mutators::MutatorConfig;
+    use crate::mutators::registry::MutatorRegistry;
+    use super::{flam3_params, from_flam3, is_flam3_param, to_flam3, FLAM3_VARIATIONS};
+
+    #[test]
+    fn test_round_trip() {
+        let julian = from_flam3("julian", 0.5, |name| if name == "julian_power" { Some(5.0) } else { None }).unwrap();
+        assert_eq!(julian, MutatorConfig::named(0.5, "julian", &[5.0, 1.0]).unwrap());
+
+        let (name, params) = to_flam3(&julian).unwrap();
+        assert_eq!(name, "julian");
+        assert_eq!(params, vec![("julian_power", 5.0), ("julian_dist", 1.0)]);
+
+        assert_eq!(to_flam3(&MutatorConfig::named(1.0, "cross", &[]).unwrap()).unwrap().0, "cross");
+        assert!(from_flam3("hypertile", 1.0, |_| None).is_none());
+        assert!(is_flam3_param("pie_slices"));
+        assert!(!is_flam3_param("hypertile_p"));
+        assert!(!is_flam3_param("wedge_julia") && !is_flam3_param("wedge_sph"));
+        assert!(is_flam3_param("wedge_julia_power"));
+
+        // weight of flam3 is a parameter, not an attribute.
+        let whorl = from_flam3("whorl", 0.7, |name| if name == "whorl_inside" { Some(2.0) } else { None }).unwrap();
+        assert_eq!(whorl.params, vec![2.0, 1.0, 0.7]);
+        assert_eq!(to_flam3(&whorl).unwrap().1, vec![("whorl_inside", 2.0), ("whorl_outside", 1.0)]);
+    }
+
+    #[test]
+    fn test_all_variations() {
+        // names are indexed by mutator id.
+        FLAM3_VARIATIONS.iter().enumerate().for_each(|(i, name)| {
+            let config = from_flam3(name, 1.0, |_| None).unwrap_or_else(|| panic!("no mutator for {name}"));
+            assert_eq!(config.mutator.id() as usize, i);
+            assert_eq!(to_flam3(&config).unwrap().0, *name);
+        });
+
+        // a flam3 param per mutator param.
+        let registry = MutatorRegistry::global().read().unwrap();
+        (0..FLAM3_VARIATIONS.len() as u8).for_each(|id| {
+            let mutator = registry.get(id).unwrap();
+            assert_eq!(flam3_params(id).len(), mutator.params().len(), "{}", mutator.name());
+        });
+    }
+}
@@ -1,19 +1,133 @@
 mod mutator_lib;
+mod builtin;
+pub(crate) mod flam3;
+pub(crate) mod param_sampler;
+pub(crate) mod expression;
+pub(crate) mod registry;
 
+use std::collections::BTreeMap;
+use std::fmt::Debug;
 use std::ops::RangeInclusive;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
 use crate::ds::affine_mat::AffineMat;
 use crate::ds::point::Point;
+use crate::ff_repository::mutator_description_service::MutatorParam;
 use crate::modnar::Modnar;
-use crate::mutators::mutator_lib::{bent, blob, cosine, diamond, disc, ex, exponential, fan, fisheye, handkerchief, heart, horseshoe, hyperbolic, julia, polar, popcorn, power, rings, sinus, spherical, spiral, swirl, waves, pdj, fan2, rings2, eyefish, bubble, cylinder, perspective, noise, julian, julias, blur, gaussian, radian_blur, pie, ngon, curl, rectangles, arch, tangent, square, rays, blade, secant, twintrian, cross};
+use crate::mutators::expression::ExpressionSource;
+use crate::mutators::registry::{MutatorError, MutatorRegistry};
 
+/// Non-linear function, applied after the affine part of a transform (flam3's variation).
+/// Registered in `MutatorRegistry` under a stable id, genomes and mutator descriptions refer to it by that.
+/// Exported by the library target, so that other crates can add their own (see `lib.rs`).
+pub trait Mutator: Debug + Send + Sync {
+    fn id(&self) -> u8;
+
+    /// Unique, genomes are saved w. it.
+    fn name(&self) -> &str;
+
+    /// Schema of the params, `apply` gets their values in this order.
+    fn params(&self) -> &[MutatorParam];
+
+    /// Draws from `rnd` on `apply`.
+    fn is_rand(&self) -> bool;
+
+    /// Reads the affine of the transform on `apply`.
+    fn requires_affine(&self) -> bool;
+
+    fn apply(&self, p: &Point, mat: &AffineMat, rnd: &mut Modnar, params: &[f32]) -> Point;
+
+    /// Source of the expression mutators, saved w. genomes (see `ExpressionMutator`).
+    fn expression(&self) -> Option<&ExpressionSource> { None }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "MutatorConfigRepr", into = "MutatorConfigRepr")]
 pub(crate) struct MutatorConfig{
-    weight: f32,
-    mutator: Mutators
+    pub(crate) weight: f32,
+    pub(crate) mutator: Arc<dyn Mutator>,
+    /// in the order of `mutator.params()`.
+    pub(crate) params: Vec<f32>
 }
 
 impl MutatorConfig {
-    pub(crate) fn new(weight: f32, mutator: Mutators) -> Self {
-        Self { weight: weight, mutator: mutator }
+    pub(crate) fn new(weight: f32, mutator: Arc<dyn Mutator>, params: Vec<f32>) -> Self {
+        assert_eq!(mutator.params().len(), params.len(), "Params of {} don't match", mutator.name());
+        Self { weight, mutator, params }
+    }
+
+    /// Mutator w. the given name from the registry, `None` if there is no such mutator.
+    /// Missing trailing `params` get their defaults.
+    pub(crate) fn named(weight: f32, name: &str, params: &[f32]) -> Option<Self> {
+        let mutator = MutatorRegistry::global().read().unwrap().by_name(name)?.clone();
+        if params.len() > mutator.params().len() {
+            return None;
+        }
+
+        let mut values = params.to_vec();
+        values.extend(mutator.params()[params.len()..].iter().map(|param| param.default_value));
+        Some(Self::new(weight, mutator, values))
+    }
+}
+
+impl PartialEq for MutatorConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.weight == other.weight && self.mutator.id() == other.mutator.id() && self.params == other.params
+    }
+}
+
+// params by name, so that reordering the schema doesn't break saved genomes.
+// Expression mutators are saved w. their source, and registered on load (provisionally), if they aren't yet.
+#[derive(Serialize, Deserialize)]
+struct MutatorConfigRepr {
+    weight: f32,
+    mutator: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    params: BTreeMap<String, f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expression: Option<ExpressionSource>
+}
+
+impl TryFrom<MutatorConfigRepr> for MutatorConfig {
+    type Error = MutatorError;
+
+    fn try_from(mut repr: MutatorConfigRepr) -> Result<Self, Self::Error> {
+        let mutator = MutatorRegistry::global().read().unwrap().by_name(&repr.mutator).cloned();
+        let mutator = match (mutator, repr.expression) {
+            (Some(mutator), None) => mutator,
+            (Some(mutator), Some(expression)) if mutator.expression() == Some(&expression) => mutator,
+            (Some(_), Some(_)) => return Err(MutatorError::ExpressionMismatch(repr.mutator)),
+            (None, Some(expression)) => {
+                // bounds are unknown, the saved values will do, until it's described.
+                let params = repr.params
+                    .iter()
+                    .map(|(name, value)| MutatorParam { name: name.clone(), lower_bound: *value, upper_bound: *value, default_value: *value })
+                    .collect();
+                MutatorRegistry::global().write().unwrap().register_provisional(&repr.mutator, expression, params)?
+            }
+            (None, None) => return Err(MutatorError::UnknownMutator(repr.mutator))
+        };
+
+        let params = mutator.params()
+            .iter()
+            .map(|param| repr.params.remove(&param.name).unwrap_or(param.default_value))
+            .collect();
+        if let Some(name) = repr.params.into_keys().next() {
+            return Err(MutatorError::UnknownParam(repr.mutator, name));
+        }
+
+        Ok(Self::new(repr.weight, mutator, params))
+    }
+}
+
+impl From<MutatorConfig> for MutatorConfigRepr {
+    fn from(config: MutatorConfig) -> Self {
+        Self {
+            weight: config.weight,
+            mutator: config.mutator.name().to_string(),
+            params: config.mutator.params().iter().map(|param| param.name.clone()).zip(config.params).collect(),
+            expression: config.mutator.expression().cloned()
+        }
     }
 }
 
@@ -24,7 +138,7 @@ pub(crate) fn apply_mutator_combination(
     rnd: &mut Modnar
 ) -> Point {
     mutators.iter().fold(Point::zero(), |acc, mutator| {
-        let app_res = call(mutator.mutator, point, mat, rnd);
+        let app_res = mutator.mutator.apply(point, mat, rnd, &mutator.params);
 
        Point::new(
            // - TODO: eh, operator overloading.
@@ -34,119 +148,56 @@ pub(crate) fn apply_mutator_combination(
     })
 }
 
+// ids of the built-ins, searched over. Linear (0) isn't: searching over the identity makes little sense,
+// it's there to express flam3 genomes.
+pub(crate) const ALL_MUTATOR_DISCRIMINANTS: RangeInclusive<u8> = 1..=98;
 
-pub(crate) const ALL_MUTATOR_DISCRIMINANTS: RangeInclusive<u8> = 1..=48;
-// same as [repr(C, u8)], u8 concerns discriminant vals, C concerns size and alignment of params.
-// https://doc.rust-lang.org/reference/type-layout.html
-#[repr(u8)]
-#[allow(unused)]
-#[derive(Copy, Clone)]
-pub(crate) enum Mutators {
-    Sinus = 1,
-    Spherical = 2,
-    Swirl = 3,
-    Horseshoe = 4,
-    Polar = 5,
-    Handkerchief = 6,
-    Heart = 7,
-    Disc = 8,
-    Spiral = 9,
-    Hyperbolic = 10,
-    Diamond = 11,
-    Ex = 12,
-    Julia = 13,
-    Bent = 14,
-    Waves = 15,
-    Fisheye = 16,
-    Popcorn = 17,
-    Exponential = 18,
-    Power = 19,
-    Cosine = 20,
-    Rings = 21,
-    Fan = 22,
-    Blob { blob_h: f32, blob_l: f32, blob_waves: f32 } = 23,
-    Pdj { pdj_a: f32, pdj_b: f32, pdj_c: f32, pdj_d: f32 } = 24,
-    Fan2 { fx: f32, fy: f32 } = 25,
-    Rings2 { rings2_val: f32 } = 26,
-    Eyefish = 27,
-    Bubble = 28,
-    Cylinder = 29,
-    Perspective { p1_angle: f32, p2_dist: f32 } = 30,
-    Noise = 31,
-    Julian { power: f32, dist: f32 } = 32,
-    Julias { power: f32, dist: f32 } = 33,
-    Blur = 34,
-    Gaussian = 35,
-    RadianBlur { angle: f32, v36: f32 } = 36,
-    Pie { slices: f32, rotation: f32, thickness: f32 } = 37,
-    Ngon { power: f32, sides: f32, corners: f32, circle: f32 } = 38,
-    Curl { c1: f32, c2: f32 } = 39,
-    Rectangles { rect_x: f32, rect_y: f32 } = 40,
-    Arch { v41: f32 } = 41,
-    Tangent = 42,
-    Square = 43,
-    Rays { v44: f32 } = 44,
-    Blade { v45: f32 } = 45,
-    Secant { v46: f32 } = 46,
-    Twintrian { v47: f32 } = 47,
-    Cross = 48
-}
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::point::Point;
+    use crate::modnar::Modnar;
+    use super::MutatorConfig;
 
-fn call(
-    mutator: Mutators,
-    p: &Point,
-    mat: &AffineMat,
-    rnd: &mut Modnar
-) -> Point {
-    match mutator {
-        Mutators::Sinus => sinus(p),
-        Mutators::Spherical => spherical(p),
-        Mutators::Swirl => swirl(p),
-        Mutators::Horseshoe => horseshoe(p),
-        Mutators::Polar => polar(p),
-        Mutators::Handkerchief => handkerchief(p),
-        Mutators::Heart => heart(p),
-        Mutators::Disc => disc(p),
-        Mutators::Spiral => spiral(p),
-        Mutators::Hyperbolic => hyperbolic(p),
-        Mutators::Diamond => diamond(p),
-        Mutators::Ex => ex(p),
-        Mutators::Julia => julia(p, rnd),
-        Mutators::Bent => bent(p),
-        // - TODO: mutators, dependent on transform applied. has something to it.
-        Mutators::Waves => waves(p, mat.b, mat.c, mat.e, mat.f),
-        Mutators::Fisheye => fisheye(p),
-        Mutators::Popcorn => popcorn(p, mat.c, mat.f),
-        Mutators::Exponential => exponential(p),
-        Mutators::Power => power(p),
-        Mutators::Cosine => cosine(p),
-        Mutators::Rings => rings(p, mat.c),
-        Mutators::Fan => fan(p, mat.c, mat.f),
-        Mutators::Blob { blob_h, blob_l, blob_waves } => blob(p, blob_h, blob_l, blob_waves),
-        Mutators::Pdj { pdj_a, pdj_b, pdj_c, pdj_d } => pdj(p, pdj_a, pdj_b, pdj_c, pdj_d),
-        Mutators::Fan2 { fx, fy } => fan2(p, fx, fy),
-        Mutators::Rings2 { rings2_val } => rings2(p, rings2_val),
-        Mutators::Eyefish => eyefish(p),
-        Mutators::Bubble => bubble(p),
-        Mutators::Cylinder => cylinder(p),
-        Mutators::Perspective { p1_angle, p2_dist } => perspective(p, p1_angle, p2_dist),
-        Mutators::Noise => noise(p, rnd),
-        Mutators::Julian { power, dist } => julian(p, rnd, power, dist),
-        Mutators::Julias { power, dist } => julias(p, rnd, power, dist),
-        Mutators::Blur => blur(p, rnd),
-        Mutators::Gaussian => gaussian(p, rnd),
-        Mutators::RadianBlur { angle, v36 } => radian_blur(p, rnd, angle, v36),
-        Mutators::Pie { slices, rotation, thickness } => pie(p, rnd, slices,rotation, thickness),
-        Mutators::Ngon { power, sides, corners, circle } => ngon(p, power, sides, corners, circle),
-        Mutators::Curl { c1, c2 } => curl(p, c1, c2),
-        Mutators::Rectangles { rect_x, rect_y } => rectangles(p, rect_x, rect_y),
-        Mutators::Arch { v41 } => arch(p, rnd, v41),
-        Mutators::Tangent => tangent(p),
-        Mutators::Square => square(p, rnd),
-        Mutators::Rays { v44 } => rays(p, rnd, v44),
-        Mutators::Blade { v45 } => blade(p, rnd, v45),
-        Mutators::Secant { v46 } => secant(p, v46),
-        Mutators::Twintrian { v47 } => twintrian(p, rnd, v47),
-        Mutators::Cross => cross(p),
+    fn apply(name: &str, params: &[f32], p: &Point) -> Point {
+        let config = MutatorConfig::named(1.0, name, params).unwrap();
+        config.mutator.apply(p, &AffineMat::identity(), &mut Modnar::new_rng_seeded(42), &config.params)
+    }
+
+    fn assert_close(lhs: &Point, rhs: &Point) {
+        assert!((lhs.x - rhs.x).abs() < 1e-5 && (lhs.y - rhs.y).abs() < 1e-5, "({}, {}) vs ({}, {})", lhs.x, lhs.y, rhs.x, rhs.y);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_flam3_variations() {
+        let p = Point::new(0.3, -0.7);
+
+        // exp and log are inverse, cos z = sin(z + pi/2).
+        assert_close(&apply("exp", &[], &apply("log", &[], &p)), &p);
+        assert_close(&apply("cos", &[], &p), &apply("sin", &[], &Point::new(p.x + std::f32::consts::FRAC_PI_2, p.y)));
+
+        assert_close(&apply("mobius", &[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0], &p), &p);
+        assert_close(&apply("bent2", &[1.0, 1.0], &p), &p);
+        assert_close(&apply("bent2", &[2.0, 0.5], &p), &apply("bent", &[], &p));
+
+        // from the unit circle outwards, loonie is the identity.
+        assert_close(&apply("loonie", &[1.0], &Point::new(1.0, 1.0)), &Point::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn test_serde() {
+        let julian = MutatorConfig::named(0.5, "julian", &[5.0]).unwrap();
+        assert_eq!(julian.params, vec![5.0, 0.31]);
+
+        let json = serde_json::to_string(&julian).unwrap();
+        assert_eq!(serde_json::from_str::<MutatorConfig>(&json).unwrap(), julian);
+
+        // missing params get defaults.
+        let parsed: MutatorConfig = serde_json::from_str(r#"{"weight": 1.0, "mutator": "julian", "params": {"dist": 2.0}}"#).unwrap();
+        assert_eq!(parsed.params, vec![5.0, 2.0]);
+
+        assert!(serde_json::from_str::<MutatorConfig>(r#"{"weight": 1.0, "mutator": "julian", "params": {"power2": 2.0}}"#).is_err());
+        assert!(serde_json::from_str::<MutatorConfig>(r#"{"weight": 1.0, "mutator": "no_such_mutator"}"#).is_err());
+        assert!(MutatorConfig::named(1.0, "swirl", &[1.0]).is_none());
+    }
+}
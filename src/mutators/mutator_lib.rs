@@ -41,6 +41,11 @@ fn lambda(rnd: &mut Modnar) -> f32 {
 }
 
 /// Mutator Lib
+#[inline(always)]
+pub(super) fn linear(p: &Point) -> Point {
+    p.clone()
+}
+
 #[inline(always)]
 pub(super) fn sinus(p: &Point) -> Point {
     Point::new(p.x.sin(), p.y.sin())
@@ -662,4 +667,581 @@ pub(super) fn cross(p: &Point) -> Point {
         p.x * v,
         p.y * v
     )
-}
\ No newline at end of file
+}
+// flam3 variations 49.., formulas as in flam3's variations.c.
+// Mutators w. a `v*` parameter use flam3's weight non-linearly: `v*` plays the weight there,
+// the result is flam3's over `v*`, the weight of the `MutatorConfig` puts it back.
+
+fn atan2(y: f32, x: f32) -> f32 { y.atan2(x) }
+fn exp(val: f32) -> f32 { val.exp() }
+fn ln(val: f32) -> f32 { val.ln() }
+
+#[inline(always)]
+pub(super) fn disc2(p: &Point, rot: f32, twist: f32) -> Point {
+    pi!(pi);
+    let turn = 2.0*pi;
+
+    let mut sin_add = sin(twist);
+    let mut cos_add = cos(twist) - 1.0;
+    if twist > turn || twist < -turn {
+        let k = if twist > turn { 1.0 + twist - turn } else { 1.0 + twist + turn };
+        sin_add *= k;
+        cos_add *= k;
+    }
+
+    let t = rot*pi*(p.x + p.y);
+    let r = atan2(p.x, p.y) / pi;
+
+    Point::new(
+        (sin(t) + cos_add)*r,
+        (cos(t) + sin_add)*r
+    )
+}
+
+#[inline(always)]
+#[allow(clippy::too_many_arguments)]
+pub(super) fn super_shape(p: &Point, rnd: &mut Modnar, m: f32, n1: f32, n2: f32, n3: f32, randomness: f32, holes: f32) -> Point {
+    pi!(pi);
+    psi!(rnd, psi);
+    r!(p, r);
+
+    let theta = m/4.0*atan2(p.y, p.x) + pi/4.0;
+    let t1 = cos(theta).abs().powf(n2);
+    let t2 = sin(theta).abs().powf(n3);
+    let k = ((randomness*psi + (1.0 - randomness)*r) - holes) * (t1 + t2).powf(-1.0/n1) / r;
+
+    Point::new(k*p.x, k*p.y)
+}
+
+#[inline(always)]
+pub(super) fn flower(p: &Point, rnd: &mut Modnar, petals: f32, holes: f32) -> Point {
+    psi!(rnd, psi);
+    r!(p, r);
+
+    let k = (psi - holes)*cos(petals*atan2(p.y, p.x)) / r;
+
+    Point::new(k*p.x, k*p.y)
+}
+
+#[inline(always)]
+pub(super) fn conic(p: &Point, rnd: &mut Modnar, eccentricity: f32, holes: f32) -> Point {
+    psi!(rnd, psi);
+    r!(p, r);
+
+    let ct = p.x / r;
+    let k = (psi - holes)*eccentricity / (1.0 + eccentricity*ct) / r;
+
+    Point::new(k*p.x, k*p.y)
+}
+
+#[inline(always)]
+pub(super) fn parabola(p: &Point, rnd: &mut Modnar, height: f32, width: f32) -> Point {
+    psi!(rnd, psi);
+    psi!(rnd, psi2);
+    r!(p, r);
+
+    Point::new(
+        height*sin(r)*sin(r)*psi,
+        width*cos(r)*psi2
+    )
+}
+
+#[inline(always)]
+pub(super) fn bent2(p: &Point, x: f32, y: f32) -> Point {
+    Point::new(
+        if p.x < 0.0 { p.x*x } else { p.x },
+        if p.y < 0.0 { p.y*y } else { p.y }
+    )
+}
+
+#[inline(always)]
+pub(super) fn bipolar(p: &Point, shift: f32) -> Point {
+    pi!(pi);
+    rsq!(p, rsq);
+
+    let t = rsq + 1.0;
+    let x2 = 2.0*p.x;
+    let mut y = 0.5*atan2(2.0*p.y, rsq - 1.0) - pi/2.0*shift;
+
+    if y > pi/2.0 {
+        y = -pi/2.0 + fmod(y + pi/2.0, pi);
+    } else if y < -pi/2.0 {
+        y = pi/2.0 - fmod(pi/2.0 - y, pi);
+    }
+
+    Point::new(
+        0.25*(2.0/pi)*ln((t + x2)/(t - x2)),
+        (2.0/pi)*y
+    )
+}
+
+#[inline(always)]
+pub(super) fn boarders(p: &Point, rnd: &mut Modnar) -> Point {
+    psi!(rnd, psi);
+
+    let (round_x, round_y) = (p.x.round(), p.y.round());
+    let (offset_x, offset_y) = (p.x - round_x, p.y - round_y);
+    let (x, y) = (offset_x*0.5 + round_x, offset_y*0.5 + round_y);
+
+    if psi >= 0.75 {
+        return Point::new(x, y);
+    }
+
+    if offset_x.abs() >= offset_y.abs() {
+        let side = if offset_x >= 0.0 { 0.25 } else { -0.25 };
+        Point::new(x + side, y + side*offset_y/offset_x)
+    } else {
+        let side = if offset_y >= 0.0 { 0.25 } else { -0.25 };
+        Point::new(x + side*offset_x/offset_y, y + side)
+    }
+}
+
+#[inline(always)]
+pub(super) fn butterfly(p: &Point) -> Point {
+    // 4/sqrt(3*pi)
+    let wx = 1.302_940_1;
+    let y2 = 2.0*p.y;
+    let r = wx*sqrt((p.x*p.y).abs() / (f32::EPSILON + p.x*p.x + y2*y2));
+
+    Point::new(r*p.x, r*y2)
+}
+
+#[inline(always)]
+pub(super) fn cell(p: &Point, size: f32) -> Point {
+    let mut x = floor(p.x/size);
+    let mut y = floor(p.y/size);
+    let dx = p.x - x*size;
+    let dy = p.y - y*size;
+
+    // interleaves the quadrants.
+    x = if x >= 0.0 { 2.0*x } else { -(2.0*x + 1.0) };
+    y = if y >= 0.0 { 2.0*y } else { -(2.0*y + 1.0) };
+
+    Point::new(
+        dx + x*size,
+        -(dy + y*size)
+    )
+}
+
+#[inline(always)]
+pub(super) fn cpow(p: &Point, rnd: &mut Modnar, re: f32, im: f32, power: f32) -> Point {
+    pi!(pi);
+    psi!(rnd, psi);
+    rsq!(p, rsq);
+
+    let a = atan2(p.y, p.x);
+    let lnr = 0.5*ln(rsq);
+    let (vc, vd) = (re/power, im/power);
+    let angle = vc*a + vd*lnr + 2.0*pi/power*floor(power*psi);
+    let m = exp(vc*lnr - vd*a);
+
+    Point::new(m*cos(angle), m*sin(angle))
+}
+
+#[inline(always)]
+pub(super) fn curve(p: &Point, xamp: f32, yamp: f32, xlength: f32, ylength: f32) -> Point {
+    let xlen = (xlength*xlength).max(1e-20);
+    let ylen = (ylength*ylength).max(1e-20);
+
+    Point::new(
+        p.x + xamp*exp(-p.y*p.y/xlen),
+        p.y + yamp*exp(-p.x*p.x/ylen)
+    )
+}
+
+#[inline(always)]
+pub(super) fn edisc(p: &Point) -> Point {
+    rsq!(p, rsq);
+
+    let tmp = rsq + 1.0;
+    let tmp2 = 2.0*p.x;
+    let xmax = (sqrt(tmp + tmp2) + sqrt(tmp - tmp2))*0.5;
+    let a1 = ln(xmax + sqrt(xmax - 1.0));
+    let a2 = -(p.x/xmax).acos();
+    let w = 1.0/11.570_346;
+
+    let snv = if p.y > 0.0 { -sin(a1) } else { sin(a1) };
+
+    Point::new(
+        w*cosh(a2)*cos(a1),
+        w*sinh(a2)*snv
+    )
+}
+
+#[inline(always)]
+pub(super) fn elliptic(p: &Point) -> Point {
+    pi!(pi);
+    rsq!(p, rsq);
+
+    let tmp = rsq + 1.0;
+    let x2 = 2.0*p.x;
+    let xmax = 0.5*(sqrt(tmp + x2) + sqrt(tmp - x2));
+    let a = p.x/xmax;
+    let b = sqrt((1.0 - a*a).max(0.0));
+    let ssx = sqrt((xmax - 1.0).max(0.0));
+    let w = 2.0/pi;
+
+    Point::new(
+        w*atan2(a, b),
+        if p.y > 0.0 { w*ln(xmax + ssx) } else { -w*ln(xmax + ssx) }
+    )
+}
+
+#[inline(always)]
+pub(super) fn escher(p: &Point, beta: f32) -> Point {
+    rsq!(p, rsq);
+
+    let a = atan2(p.y, p.x);
+    let lnr = 0.5*ln(rsq);
+    let vc = 0.5*(1.0 + cos(beta));
+    let vd = 0.5*sin(beta);
+    let m = exp(vc*lnr - vd*a);
+    let n = vc*a + vd*lnr;
+
+    Point::new(m*cos(n), m*sin(n))
+}
+
+#[inline(always)]
+pub(super) fn foci(p: &Point) -> Point {
+    let expx = exp(p.x)*0.5;
+    let expnx = 0.25/expx;
+    let tmp = 1.0/(expx + expnx - cos(p.y));
+
+    Point::new(
+        tmp*(expx - expnx),
+        tmp*sin(p.y)
+    )
+}
+
+#[inline(always)]
+pub(super) fn lazysusan(p: &Point, spin: f32, space: f32, twist: f32, x: f32, y: f32, v65: f32) -> Point {
+    let dx = p.x - x;
+    let dy = p.y + y;
+    let r = sqrt(dx*dx + dy*dy);
+
+    if r < v65 {
+        let a = atan2(dy, dx) + spin + twist*(v65 - r);
+        Point::new(
+            r*cos(a) + x/v65,
+            r*sin(a) - y/v65
+        )
+    } else {
+        let k = 1.0 + space/r;
+        Point::new(
+            k*dx + x/v65,
+            k*dy - y/v65
+        )
+    }
+}
+
+#[inline(always)]
+pub(super) fn loonie(p: &Point, v66: f32) -> Point {
+    rsq!(p, rsq);
+    let w2 = v66*v66;
+
+    if rsq < w2 && rsq != 0.0 {
+        let r = sqrt(w2/rsq - 1.0);
+        Point::new(r*p.x, r*p.y)
+    } else {
+        p.clone()
+    }
+}
+
+/// flam3 blurs the input of the other variations of the xform,
+/// as a mutator of its own it's the point w. a gaussian blur around it.
+#[inline(always)]
+pub(super) fn pre_blur(p: &Point, rnd: &mut Modnar) -> Point {
+    pi!(pi);
+    psi!(rnd, psi);
+    psi!(rnd, psi2);
+    psi!(rnd, psi3);
+    psi!(rnd, psi4);
+    psi!(rnd, psi5);
+
+    let g = (psi+psi2+psi3+psi4) - 2.0;
+    let a = 2.0*pi*psi5;
+
+    Point::new(
+        p.x + g*cos(a),
+        p.y + g*sin(a)
+    )
+}
+
+#[inline(always)]
+pub(super) fn modulus(p: &Point, x: f32, y: f32) -> Point {
+    let wrap = |v: f32, m: f32| {
+        if v > m {
+            -m + fmod(v + m, 2.0*m)
+        } else if v < -m {
+            m - fmod(m - v, 2.0*m)
+        } else {
+            v
+        }
+    };
+
+    Point::new(wrap(p.x, x), wrap(p.y, y))
+}
+
+#[inline(always)]
+pub(super) fn oscilloscope(p: &Point, separation: f32, frequency: f32, amplitude: f32, damping: f32) -> Point {
+    pi!(pi);
+
+    let t = amplitude*exp(-p.x.abs()*damping)*cos(2.0*pi*frequency*p.x) + separation;
+
+    Point::new(
+        p.x,
+        if p.y.abs() <= t { -p.y } else { p.y }
+    )
+}
+
+#[inline(always)]
+pub(super) fn polar2(p: &Point) -> Point {
+    pi!(pi);
+    rsq!(p, rsq);
+
+    Point::new(
+        atan2(p.x, p.y)/pi,
+        ln(rsq)/(2.0*pi)
+    )
+}
+
+#[inline(always)]
+pub(super) fn popcorn2(p: &Point, x: f32, y: f32, c: f32) -> Point {
+    Point::new(
+        p.x + x*sin(tan(p.y*c)),
+        p.y + y*sin(tan(p.x*c))
+    )
+}
+
+#[inline(always)]
+pub(super) fn scry(p: &Point, v72: f32) -> Point {
+    rsq!(p, rsq);
+
+    let r = 1.0/(sqrt(rsq)*(rsq + 1.0/(v72 + f32::EPSILON))) / v72;
+
+    Point::new(r*p.x, r*p.y)
+}
+
+#[inline(always)]
+pub(super) fn separation(p: &Point, x: f32, xinside: f32, y: f32, yinside: f32) -> Point {
+    let part = |v: f32, s: f32, inside: f32| {
+        if v > 0.0 {
+            sqrt(v*v + s*s) - v*inside
+        } else {
+            -(sqrt(v*v + s*s) + v*inside)
+        }
+    };
+
+    Point::new(part(p.x, x, xinside), part(p.y, y, yinside))
+}
+
+#[inline(always)]
+pub(super) fn split(p: &Point, xsize: f32, ysize: f32) -> Point {
+    pi!(pi);
+
+    Point::new(
+        if cos(p.y*ysize*pi) >= 0.0 { p.x } else { -p.x },
+        if cos(p.x*xsize*pi) >= 0.0 { p.y } else { -p.y }
+    )
+}
+
+#[inline(always)]
+pub(super) fn splits(p: &Point, x: f32, y: f32) -> Point {
+    Point::new(
+        if p.x >= 0.0 { p.x + x } else { p.x - x },
+        if p.y >= 0.0 { p.y + y } else { p.y - y }
+    )
+}
+
+#[inline(always)]
+pub(super) fn stripes(p: &Point, space: f32, warp: f32) -> Point {
+    let round_x = floor(p.x + 0.5);
+    let offset_x = p.x - round_x;
+
+    Point::new(
+        offset_x*(1.0 - space) + round_x,
+        p.y + offset_x*offset_x*warp
+    )
+}
+
+// shared by the wedges: `a` cut into `count` sectors, each one narrowed by `angle`.
+#[inline(always)]
+fn wedge_angle(a: f32, angle: f32, count: f32) -> f32 {
+    pi!(pi);
+
+    let c = floor((count*a + pi)/pi*0.5);
+    let comp_fac = 1.0 - angle*count/pi*0.5;
+    a*comp_fac + c*angle
+}
+
+#[inline(always)]
+pub(super) fn wedge(p: &Point, angle: f32, hole: f32, count: f32, swirl: f32) -> Point {
+    r!(p, r);
+
+    let a = wedge_angle(atan2(p.y, p.x) + swirl*r, angle, count);
+    let r = r + hole;
+
+    Point::new(r*cos(a), r*sin(a))
+}
+
+#[inline(always)]
+pub(super) fn wedge_julia(p: &Point, rnd: &mut Modnar, angle: f32, count: f32, power: f32, dist: f32) -> Point {
+    pi!(pi);
+    psi!(rnd, psi);
+    rsq!(p, rsq);
+
+    let r = rsq.powf(dist/power/2.0);
+    let t = trunc(power.abs()*psi);
+    let a = wedge_angle((atan2(p.y, p.x) + 2.0*pi*t)/power, angle, count);
+
+    Point::new(r*cos(a), r*sin(a))
+}
+
+#[inline(always)]
+pub(super) fn wedge_sph(p: &Point, angle: f32, count: f32, hole: f32, swirl: f32) -> Point {
+    r!(p, r);
+
+    let r = 1.0/(r + f32::EPSILON);
+    let a = wedge_angle(atan2(p.y, p.x) + swirl*r, angle, count);
+    let r = r + hole;
+
+    Point::new(r*cos(a), r*sin(a))
+}
+
+#[inline(always)]
+pub(super) fn whorl(p: &Point, inside: f32, outside: f32, v80: f32) -> Point {
+    r!(p, r);
+
+    let twist = if r < v80 { inside } else { outside };
+    let a = atan2(p.y, p.x) + twist/(v80 - r);
+
+    Point::new(r*cos(a), r*sin(a))
+}
+
+#[inline(always)]
+pub(super) fn waves2(p: &Point, freqx: f32, scalex: f32, freqy: f32, scaley: f32) -> Point {
+    Point::new(
+        p.x + scalex*sin(p.y*freqx),
+        p.y + scaley*sin(p.x*freqy)
+    )
+}
+
+// complex functions of z = x + iy.
+#[inline(always)]
+pub(super) fn cexp(p: &Point) -> Point {
+    let e = exp(p.x);
+    Point::new(e*cos(p.y), e*sin(p.y))
+}
+
+#[inline(always)]
+pub(super) fn clog(p: &Point) -> Point {
+    rsq!(p, rsq);
+    Point::new(0.5*ln(rsq), atan2(p.y, p.x))
+}
+
+#[inline(always)]
+pub(super) fn csin(p: &Point) -> Point {
+    Point::new(sin(p.x)*cosh(p.y), cos(p.x)*sinh(p.y))
+}
+
+#[inline(always)]
+pub(super) fn ccos(p: &Point) -> Point {
+    Point::new(cos(p.x)*cosh(p.y), -sin(p.x)*sinh(p.y))
+}
+
+#[inline(always)]
+pub(super) fn ctan(p: &Point) -> Point {
+    let den = 1.0/(cos(2.0*p.x) + cosh(2.0*p.y));
+    Point::new(den*sin(2.0*p.x), den*sinh(2.0*p.y))
+}
+
+#[inline(always)]
+pub(super) fn csec(p: &Point) -> Point {
+    let den = 2.0/(cos(2.0*p.x) + cosh(2.0*p.y));
+    Point::new(den*cos(p.x)*cosh(p.y), den*sin(p.x)*sinh(p.y))
+}
+
+#[inline(always)]
+pub(super) fn ccsc(p: &Point) -> Point {
+    let den = 2.0/(cosh(2.0*p.y) - cos(2.0*p.x));
+    Point::new(den*sin(p.x)*cosh(p.y), -den*cos(p.x)*sinh(p.y))
+}
+
+#[inline(always)]
+pub(super) fn ccot(p: &Point) -> Point {
+    let den = 1.0/(cosh(2.0*p.y) - cos(2.0*p.x));
+    Point::new(den*sin(2.0*p.x), -den*sinh(2.0*p.y))
+}
+
+#[inline(always)]
+pub(super) fn csinh(p: &Point) -> Point {
+    Point::new(sinh(p.x)*cos(p.y), cosh(p.x)*sin(p.y))
+}
+
+#[inline(always)]
+pub(super) fn ccosh(p: &Point) -> Point {
+    Point::new(cosh(p.x)*cos(p.y), sinh(p.x)*sin(p.y))
+}
+
+#[inline(always)]
+pub(super) fn ctanh(p: &Point) -> Point {
+    let den = 1.0/(cos(2.0*p.y) + cosh(2.0*p.x));
+    Point::new(den*sinh(2.0*p.x), den*sin(2.0*p.y))
+}
+
+#[inline(always)]
+pub(super) fn csech(p: &Point) -> Point {
+    let den = 2.0/(cos(2.0*p.y) + cosh(2.0*p.x));
+    Point::new(den*cos(p.y)*cosh(p.x), -den*sin(p.y)*sinh(p.x))
+}
+
+#[inline(always)]
+pub(super) fn ccsch(p: &Point) -> Point {
+    let den = 2.0/(cosh(2.0*p.x) - cos(2.0*p.y));
+    Point::new(den*sinh(p.x)*cos(p.y), -den*cosh(p.x)*sin(p.y))
+}
+
+#[inline(always)]
+pub(super) fn ccoth(p: &Point) -> Point {
+    let den = 1.0/(cosh(2.0*p.x) - cos(2.0*p.y));
+    Point::new(den*sinh(2.0*p.x), den*sin(2.0*p.y))
+}
+
+#[inline(always)]
+pub(super) fn auger(p: &Point, freq: f32, auger_weight: f32, sym: f32, scale: f32) -> Point {
+    let s = sin(freq*p.x);
+    let t = sin(freq*p.y);
+    let dy = p.y + auger_weight*(scale*s/2.0 + p.y.abs()*s);
+    let dx = p.x + auger_weight*(scale*t/2.0 + p.x.abs()*t);
+
+    Point::new(
+        p.x + sym*(dx - p.x),
+        dy
+    )
+}
+
+#[inline(always)]
+pub(super) fn flux(p: &Point, spread: f32, v97: f32) -> Point {
+    let xpw = p.x + v97;
+    let xmw = p.x - v97;
+    let avgr = (2.0 + spread)*sqrt(sqrt(p.y*p.y + xpw*xpw) / sqrt(p.y*p.y + xmw*xmw));
+    let avga = (atan2(p.y, xmw) - atan2(p.y, xpw))*0.5;
+
+    Point::new(avgr*cos(avga), avgr*sin(avga))
+}
+
+/// (a z + b) / (c z + d), complex a..d.
+#[inline(always)]
+#[allow(clippy::too_many_arguments)]
+pub(super) fn mobius(p: &Point, re_a: f32, im_a: f32, re_b: f32, im_b: f32, re_c: f32, im_c: f32, re_d: f32, im_d: f32) -> Point {
+    let re_u = re_a*p.x - im_a*p.y + re_b;
+    let im_u = re_a*p.y + im_a*p.x + im_b;
+    let re_v = re_c*p.x - im_c*p.y + re_d;
+    let im_v = re_c*p.y + im_c*p.x + im_d;
+    let rad_v = 1.0/(re_v*re_v + im_v*im_v);
+
+    Point::new(
+        rad_v*(re_u*re_v + im_u*im_v),
+        rad_v*(im_u*re_v - re_u*im_v)
+    )
+}
@@ -0,0 +1,100 @@
+use crate::ff_repository::mutator_description_service::MutatorParam;
+use crate::modnar::Modnar;
+
+/// How mutator parameters are drawn within their `MutatorParam` bounds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) enum ParamDistribution {
+    #[default]
+    Uniform,
+    /// Gaussian around `default_value`, sigma - `spread` of the bounds width. Clamped to the bounds.
+    Normal { spread: f32 },
+    /// Peaks at `default_value` (clamped to the bounds), falls off linearly to the bounds.
+    Triangular
+}
+
+/// Seeded sampler of mutator parameters: same seed, same draws.
+pub(crate) struct ParamSampler {
+    distribution: ParamDistribution,
+    rnd: Modnar
+}
+
+impl ParamSampler {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { distribution: ParamDistribution::default(), rnd: Modnar::new_rng_seeded(seed) }
+    }
+
+    pub(crate) fn with_distribution(mut self, distribution: ParamDistribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    pub(crate) fn sample(&mut self, param: &MutatorParam) -> f32 {
+        let (lo, hi) = (param.lower_bound, param.upper_bound);
+        if hi <= lo {
+            return lo;
+        }
+
+        match self.distribution {
+            ParamDistribution::Uniform => lo + (hi - lo) * self.rnd.gen_f32(),
+            ParamDistribution::Normal { spread } => {
+                // Box-Muller. 1 - u, so that ln doesn't get 0.
+                let u1 = 1.0 - self.rnd.gen_f32();
+                let u2 = self.rnd.gen_f32();
+                let z = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+
+                (param.default_value + z * spread * (hi - lo)).clamp(lo, hi)
+            }
+            ParamDistribution::Triangular => {
+                // inverse CDF.
+                let mode = param.default_value.clamp(lo, hi);
+                let u = self.rnd.gen_f32();
+                let split = (mode - lo) / (hi - lo);
+
+                if u < split {
+                    lo + (u * (hi - lo) * (mode - lo)).sqrt()
+                } else {
+                    hi - ((1.0 - u) * (hi - lo) * (hi - mode)).sqrt()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ff_repository::mutator_description_service::MutatorParam;
+    use super::{ParamDistribution, ParamSampler};
+
+    fn param() -> MutatorParam {
+        MutatorParam { name: "power".to_string(), lower_bound: -2.0, upper_bound: 6.0, default_value: 4.0 }
+    }
+
+    fn samples(distribution: ParamDistribution, seed: u64) -> Vec<f32> {
+        let mut sampler = ParamSampler::new(seed).with_distribution(distribution);
+        (0..10_000).map(|_| sampler.sample(&param())).collect()
+    }
+
+    fn mean(samples: &[f32]) -> f32 {
+        samples.iter().sum::<f32>() / samples.len() as f32
+    }
+
+    #[test]
+    fn test_within_bounds() {
+        for distribution in [ParamDistribution::Uniform, ParamDistribution::Normal { spread: 0.5 }, ParamDistribution::Triangular] {
+            let samples = samples(distribution.clone(), 42);
+            assert!(samples.iter().all(|s| (-2.0..=6.0).contains(s)), "{distribution:?}");
+            assert_eq!(samples, self::samples(distribution, 42));
+        }
+    }
+
+    #[test]
+    fn test_distributions() {
+        assert!((mean(&samples(ParamDistribution::Uniform, 42)) - 2.0).abs() < 0.1);
+        // (lo + mode + hi) / 3
+        assert!((mean(&samples(ParamDistribution::Triangular, 42)) - 8.0 / 3.0).abs() < 0.1);
+
+        let normal = samples(ParamDistribution::Normal { spread: 0.01 }, 42);
+        assert!(normal.iter().all(|s| (s - 4.0).abs() < 0.5));
+        assert_ne!(samples(ParamDistribution::Uniform, 42), samples(ParamDistribution::Uniform, 43));
+    }
+}
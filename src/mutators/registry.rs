@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::sync::{Arc, OnceLock, RwLock};
+use crate::ff_repository::mutator_description_service::MutatorParam;
+use crate::mutators::builtin::builtins;
+use crate::mutators::expression::{ExpressionError, ExpressionMutator, ExpressionSource};
+use crate::mutators::Mutator;
+
+#[derive(Debug, PartialEq)]
+pub enum MutatorError {
+    IdTaken(u8),
+    NameTaken(String),
+    NoFreeId,
+    UnknownMutator(String),
+    /// mutator, param.
+    UnknownParam(String, String),
+    /// registered w. another expression.
+    ExpressionMismatch(String),
+    Expression(ExpressionError)
+}
+
+impl Display for MutatorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MutatorError::IdTaken(id) => write!(f, "mutator id {id} is taken"),
+            MutatorError::NameTaken(name) => write!(f, "mutator {name} already exists"),
+            MutatorError::NoFreeId => write!(f, "no free mutator ids"),
+            MutatorError::UnknownMutator(name) => write!(f, "no mutator {name}"),
+            MutatorError::UnknownParam(name, param) => write!(f, "no param {param} for mutator {name}"),
+            MutatorError::ExpressionMismatch(name) => write!(f, "mutator {name} has another expression"),
+            MutatorError::Expression(error) => write!(f, "{error}")
+        }
+    }
+}
+
+impl Error for MutatorError {}
+
+impl From<ExpressionError> for MutatorError {
+    fn from(error: ExpressionError) -> Self {
+        MutatorError::Expression(error)
+    }
+}
+
+/// Mutators by id. Ids are stable: genomes, mutator descriptions (see `mut_desc.json`) and flam3's variations
+/// (see `flam3::FLAM3_VARIATIONS`) refer to mutators by them. The built-ins take 0..=98.
+///
+/// To add a mutator - implement `Mutator` and `register` it in the `global` registry under a free id
+/// (from another crate - via the library target, see `lib.rs`).
+#[derive(Debug)]
+pub struct MutatorRegistry {
+    by_id: BTreeMap<u8, Arc<dyn Mutator>>,
+    /// Ids of the expression mutators, which came w. a loaded genome, see `register_provisional`.
+    provisional: BTreeSet<u8>
+}
+
+impl MutatorRegistry {
+    pub(crate) fn builtin() -> Self {
+        let mut registry = Self { by_id: BTreeMap::new(), provisional: BTreeSet::new() };
+        builtins()
+            .into_iter()
+            .for_each(|mutator| registry.register(Arc::new(mutator)).expect("Built-in mutators clash!"));
+
+        registry
+    }
+
+    /// Shared by genome (de)serialization, flam3 import and the search. Starts w. the built-ins.
+    pub fn global() -> &'static RwLock<MutatorRegistry> {
+        static GLOBAL: OnceLock<RwLock<MutatorRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| RwLock::new(Self::builtin()))
+    }
+
+    /// Fails if the id or the name of `mutator` is taken. Provisional mutators give way instead.
+    pub fn register(&mut self, mutator: Arc<dyn Mutator>) -> Result<(), MutatorError> {
+        let superseded: Vec<u8> = self.provisional
+            .iter()
+            .copied()
+            .filter(|id| *id == mutator.id() || self.by_id[id].name() == mutator.name())
+            .collect();
+        for id in superseded {
+            self.by_id.remove(&id);
+            self.provisional.remove(&id);
+        }
+
+        if self.by_id.contains_key(&mutator.id()) {
+            return Err(MutatorError::IdTaken(mutator.id()));
+        }
+        if self.by_name(mutator.name()).is_some() {
+            return Err(MutatorError::NameTaken(mutator.name().to_string()));
+        }
+
+        self.by_id.insert(mutator.id(), mutator);
+        Ok(())
+    }
+
+    /// Compiles the expression and registers it under `id`, `None` - under a free one.
+    /// `params` - in the order, the expression params are given values in.
+    pub(crate) fn register_expression(
+        &mut self,
+        id: Option<u8>,
+        name: &str,
+        expression: ExpressionSource,
+        params: Vec<MutatorParam>
+    ) -> Result<Arc<dyn Mutator>, MutatorError> {
+        let id = match id {
+            Some(id) => id,
+            None => self.free_id().ok_or(MutatorError::NoFreeId)?
+        };
+        let mutator: Arc<dyn Mutator> = Arc::new(ExpressionMutator::compile(id, name, expression, params)?);
+
+        self.register(mutator.clone())?;
+        Ok(mutator)
+    }
+
+    /// Expression mutator of a genome, which isn't described (yet), so its bounds are unknown.
+    /// It's registered under a free id, until a described one w. the same name (or id) replaces it
+    /// (see `MutatorDescriptionService::load`). Genomes, loaded before that, keep it.
+    pub(crate) fn register_provisional(
+        &mut self,
+        name: &str,
+        expression: ExpressionSource,
+        params: Vec<MutatorParam>
+    ) -> Result<Arc<dyn Mutator>, MutatorError> {
+        let mutator = self.register_expression(None, name, expression, params)?;
+        self.provisional.insert(mutator.id());
+        Ok(mutator)
+    }
+
+    pub(crate) fn is_provisional(&self, id: u8) -> bool {
+        self.provisional.contains(&id)
+    }
+
+    pub fn get(&self, id: u8) -> Option<&Arc<dyn Mutator>> {
+        self.by_id.get(&id)
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&Arc<dyn Mutator>> {
+        self.by_id.values().find(|mutator| mutator.name() == name)
+    }
+
+    /// The one after the last id taken.
+    pub fn free_id(&self) -> Option<u8> {
+        match self.by_id.keys().next_back() {
+            Some(id) => id.checked_add(1),
+            None => Some(0)
+        }
+    }
+
+    /// By id.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Mutator>> {
+        self.by_id.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::point::Point;
+    use crate::ff_repository::mutator_description_service::MutatorParam;
+    use crate::modnar::Modnar;
+    use crate::mutators::expression::ExpressionSource;
+    use crate::mutators::{Mutator, MutatorConfig, ALL_MUTATOR_DISCRIMINANTS};
+    use super::{MutatorError, MutatorRegistry};
+
+    // as a module outside of `mutators` would have it.
+    #[derive(Debug)]
+    struct Shift {
+        params: Vec<MutatorParam>
+    }
+
+    impl Mutator for Shift {
+        fn id(&self) -> u8 { 200 }
+
+        fn name(&self) -> &str { "shift" }
+
+        fn params(&self) -> &[MutatorParam] { &self.params }
+
+        fn is_rand(&self) -> bool { false }
+
+        fn requires_affine(&self) -> bool { false }
+
+        fn apply(&self, p: &Point, _mat: &AffineMat, _rnd: &mut Modnar, params: &[f32]) -> Point {
+            Point::new(p.x + params[0], p.y)
+        }
+    }
+
+    #[test]
+    fn test_builtin() {
+        let registry = MutatorRegistry::builtin();
+
+        let ids: Vec<u8> = registry.iter().map(|mutator| mutator.id()).collect();
+        assert_eq!(ids, (0..=*ALL_MUTATOR_DISCRIMINANTS.end()).collect::<Vec<u8>>());
+        assert_eq!(registry.by_name("julian").unwrap().id(), 32);
+        assert_eq!(registry.free_id(), Some(99));
+        assert!(registry.iter().all(|mutator| registry.by_name(mutator.name()).unwrap().id() == mutator.id()));
+        assert!(registry.iter().flat_map(|mutator| mutator.params()).all(|param| (param.lower_bound..=param.upper_bound).contains(&param.default_value)));
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = MutatorRegistry::builtin();
+        let shift = Shift { params: vec![MutatorParam { name: String::from("dx"), lower_bound: -1.0, upper_bound: 1.0, default_value: 0.5 }] };
+        registry.register(Arc::new(shift)).unwrap();
+
+        let config = MutatorConfig::new(1.0, registry.get(200).unwrap().clone(), vec![0.5]);
+        let p = config.mutator.apply(&Point::new(0.0, 1.0), &AffineMat::identity(), &mut Modnar::new_rng_seeded(42), &config.params);
+        assert_eq!((p.x, p.y), (0.5, 1.0));
+        assert_eq!(registry.free_id(), Some(201));
+
+        let taken = registry.register(Arc::new(Shift { params: Vec::new() }));
+        assert!(matches!(taken, Err(MutatorError::IdTaken(200))));
+
+        let taken = registry.register_expression(None, "swirl", ExpressionSource::new("x", "y"), Vec::new());
+        assert!(matches!(taken, Err(MutatorError::NameTaken(_))));
+        let broken = registry.register_expression(None, "broken", ExpressionSource::new("x +", "y"), Vec::new());
+        assert!(matches!(broken, Err(MutatorError::Expression(_))));
+        assert!(registry.by_name("broken").is_none());
+    }
+}
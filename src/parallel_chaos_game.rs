@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::thread;
+use crate::chaos_game::{AffineTransformProvider, ChaosGame, RunStats};
+use crate::ds::color_histogram::ColorHistogram;
+use crate::statistics::camera::Camera;
+
+/// Splits the iteration budget into fixed size chunks, and plays the chaos game
+/// for each chunk on one of the worker threads.
+///
+/// Each chunk gets its own `ChaosGame`, seeded from (seed, chunk index), and its own histogram.
+/// Chunk histograms are merged in chunk order, so that the result depends only on the seed,
+/// not on the number of threads or on which thread got which chunk.
+/// (Color sums are floats, and float addition is not associative.)
+///
+/// Chunks aren't played more than `threads` ahead of the merge, so that at most ~3 histograms per thread
+/// (in play, in the channel, parked) are around at any time: memory is bounded by the image size, not the iterations.
+pub(crate) struct ParallelChaosGame {
+    seed: u64,
+    threads: usize
+}
+
+impl ParallelChaosGame {
+    const CHUNK_ITERATIONS: u32 = 1_000_000;
+
+    /// `threads` = 0 - use all the cores.
+    pub(crate) fn new(seed: u64, threads: usize) -> Self {
+        let threads = if threads == 0 {
+            thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+        } else {
+            threads
+        };
+
+        Self { seed, threads }
+    }
+
+    pub(crate) fn threads(&self) -> usize { self.threads }
+
+    /// Histogram, and stats of all the chunks together.
+    pub(crate) fn run_chaos_game_streaming<P>(
+        &self,
+        aff_t_provider: &P,
+        iterations: u32,
+        camera: &Camera
+    ) -> (ColorHistogram, RunStats)
+    where
+        P: AffineTransformProvider + Sync
+    {
+        self.run_chunked(iterations, camera.width, camera.height, Self::CHUNK_ITERATIONS, |chaos_game, chunk_iterations| {
+            chaos_game.run_chaos_game_streaming(aff_t_provider, chunk_iterations, camera)
+        })
+    }
+
+    fn run_chunked<F>(
+        &self,
+        iterations: u32,
+        width: usize,
+        height: usize,
+        chunk_iterations: u32,
+        play_chunk: F
+    ) -> (ColorHistogram, RunStats)
+    where
+        F: Fn(&mut ChaosGame, u32) -> ColorHistogram + Sync
+    {
+        let chunks = iterations.div_ceil(chunk_iterations);
+        let next_chunk = AtomicU32::new(0);
+        // next chunk to merge.
+        let merged = (Mutex::new(0u32), Condvar::new());
+        let mut res = ColorHistogram::new(width, height);
+        let mut stats = RunStats::default();
+        let workers = self.threads.min(chunks.max(1) as usize);
+
+        thread::scope(|scope| {
+            let (tx, rx) = mpsc::sync_channel::<(u32, ColorHistogram, RunStats)>(workers);
+
+            for _ in 0..workers {
+                let tx = tx.clone();
+                let next_chunk = &next_chunk;
+                let merged = &merged;
+                let play_chunk = &play_chunk;
+
+                scope.spawn(move || {
+                    loop {
+                        let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        if chunk >= chunks {
+                            break;
+                        }
+
+                        // the chunk, being merged next, is always within the window (claimed, and played w/o waiting).
+                        let (lock, cvar) = merged;
+                        drop(cvar.wait_while(lock.lock().unwrap(), |merged| chunk >= *merged + workers as u32).unwrap());
+
+                        let budget = chunk_iterations.min(iterations - chunk * chunk_iterations);
+                        let mut chaos_game = ChaosGame::new_seeded(Self::chunk_seed(self.seed, chunk));
+                        let hist = play_chunk(&mut chaos_game, budget);
+
+                        if tx.send((chunk, hist, chaos_game.stats())).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+
+            // merge strictly in chunk order, park the ones that came early.
+            let mut parked = BTreeMap::<u32, ColorHistogram>::new();
+            let mut next_to_merge = 0;
+
+            for (chunk, hist, chunk_stats) in rx.iter() {
+                parked.insert(chunk, hist);
+                stats.merge(&chunk_stats);
+
+                debug_assert!(parked.len() <= workers);
+
+                while let Some(hist) = parked.remove(&next_to_merge) {
+                    res.merge(&hist);
+                    next_to_merge += 1;
+                }
+
+                let (lock, cvar) = &merged;
+                *lock.lock().unwrap() = next_to_merge;
+                cvar.notify_all();
+            }
+        });
+
+        (res, stats)
+    }
+
+    // splitmix64 finalizer, so that adjacent chunks get unrelated seeds.
+    fn chunk_seed(seed: u64, chunk: u32) -> u64 {
+        let mut z = seed.wrapping_add((chunk as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chaos_game::RunStats;
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::aff_ifs::ChaosGamePreprocess;
+    use crate::ds::array_2d::Index2D;
+    use crate::ds::color_histogram::ColorHistogram;
+    use crate::ds::ifs_transform::IfsTransform;
+    use crate::statistics::camera::Camera;
+    use crate::statistics::plane::Range2D;
+    use super::ParallelChaosGame;
+
+    fn render(seed: u64, threads: usize) -> (ColorHistogram, RunStats) {
+        let mut ifs = vec![
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.33, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 0.33, 0.5, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.5), 0.33, 1.0, 0.5),
+        ];
+        ifs.prepare_for_chaos_game(false);
+
+        let camera = Camera::fit(&Range2D::new(0.0..=1.0, 0.0..=1.0), 32, 32);
+        ParallelChaosGame::new(seed, threads).run_chunked(50_000, 32, 32, 7_000, |chaos_game, budget| {
+            chaos_game.run_chaos_game_streaming(&ifs, budget, &camera)
+        })
+    }
+
+    #[test]
+    fn test_same_seed_same_image_regardless_of_threads() {
+        let (single, single_stats) = render(42, 1);
+        let (multi, multi_stats) = render(42, 4);
+
+        // 8 chunks, 20 iterations of each are the fuse.
+        assert_eq!(single.total_hits(), 50_000 - 8 * 20);
+        assert_eq!(single_stats, RunStats { iterations: 50_000, bad_values: 0 });
+        assert_eq!(single_stats, multi_stats);
+        assert_eq!(single.total_hits(), multi.total_hits());
+
+        for x in 0..32 {
+            for y in 0..32 {
+                let index = Index2D::from(x, y);
+                assert_eq!(single.hits(index), multi.hits(index));
+                assert_eq!(single.color(index), multi.color(index));
+            }
+        }
+    }
+
+    #[test]
+    fn test_many_chunks_few_threads() {
+        // 50 chunks over 3 threads: workers wait for the merge to catch up.
+        let render = |threads| {
+            let mut ifs = vec![
+                IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.5, 0.0, 0.5),
+                IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.5), 0.5, 1.0, 0.5),
+            ];
+            ifs.prepare_for_chaos_game(false);
+
+            let camera = Camera::fit(&Range2D::new(0.0..=1.0, 0.0..=1.0), 8, 8);
+            ParallelChaosGame::new(7, threads).run_chunked(50_000, 8, 8, 1_000, |chaos_game, budget| {
+                chaos_game.run_chaos_game_streaming(&ifs, budget, &camera)
+            }).0
+        };
+
+        let (single, multi) = (render(1), render(3));
+        assert_eq!(single.total_hits(), multi.total_hits());
+        assert!((0..8).all(|x| (0..8).all(|y| single.hits(Index2D::from(x, y)) == multi.hits(Index2D::from(x, y)))));
+    }
+
+    #[test]
+    fn test_different_seed_different_image() {
+        let (lhs, _) = render(1, 2);
+        let (rhs, _) = render(2, 2);
+
+        let differs = (0..32)
+            .flat_map(|x| (0..32).map(move |y| Index2D::from(x, y)))
+            .any(|index| lhs.hits(index) != rhs.hits(index));
+
+        assert!(differs);
+    }
+}
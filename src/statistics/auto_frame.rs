@@ -0,0 +1,115 @@
+use crate::statistics::camera::Camera;
+use crate::statistics::plane::Range2D;
+
+/// Picks a camera for a set of samples (usually - a short pilot run of the chaos game).
+///
+/// Raw min/max is at the mercy of a single outlier (i.e. `spherical` near the origin),
+/// so the frame is the [lower..upper] percentile range on each axis instead.
+#[derive(Clone, Debug)]
+pub(crate) struct AutoFrame {
+    /// In [0..1], i.e. 0.005 is 0.5%.
+    pub(crate) lower_percentile: f32,
+    pub(crate) upper_percentile: f32,
+    /// Fraction of the frame size, added on each side.
+    pub(crate) padding: f32,
+    /// `true` - output size is kept, and the frame is fit into it (centered).
+    /// `false` - output width is kept, height follows the aspect ratio of the frame.
+    pub(crate) lock_aspect: bool
+}
+
+impl Default for AutoFrame {
+    fn default() -> Self {
+        Self { lower_percentile: 0.005, upper_percentile: 0.995, padding: 0.05, lock_aspect: true }
+    }
+}
+
+impl AutoFrame {
+    /// `data` - samples, as returned by `ChaosGame::run_chaos_game` (x, y pairs).
+    pub(crate) fn frame(&self, data: &[f32], width: usize, height: usize) -> Camera {
+        assert!(self.lower_percentile < self.upper_percentile);
+
+        let region = self.region(data);
+        let region = Self::pad(&region, self.padding);
+
+        if self.lock_aspect {
+            return Camera::fit(&region, width, height);
+        }
+
+        let len_x = (region.x_range().end() - region.x_range().start()).max(f32::EPSILON);
+        let len_y = region.y_range().end() - region.y_range().start();
+        let height = ((width as f32 * len_y / len_x).round() as usize).max(1);
+
+        Camera::fit(&region, width, height)
+    }
+
+    /// Percentile range on each axis, non-finite samples are ignored.
+    pub(crate) fn region(&self, data: &[f32]) -> Range2D {
+        let mut xs: Vec<f32> = data.iter().step_by(2).copied().filter(|x| x.is_finite()).collect();
+        let mut ys: Vec<f32> = data.iter().skip(1).step_by(2).copied().filter(|y| y.is_finite()).collect();
+
+        if xs.is_empty() || ys.is_empty() {
+            return Range2D::new(-1.0..=1.0, -1.0..=1.0);
+        }
+
+        Range2D::new(
+            self.percentile(&mut xs, self.lower_percentile)..=self.percentile(&mut xs, self.upper_percentile),
+            self.percentile(&mut ys, self.lower_percentile)..=self.percentile(&mut ys, self.upper_percentile)
+        )
+    }
+
+    fn percentile(&self, values: &mut [f32], percentile: f32) -> f32 {
+        let idx = ((values.len() - 1) as f32 * percentile.clamp(0.0, 1.0)).round() as usize;
+        *values.select_nth_unstable_by(idx, |lhs, rhs| lhs.total_cmp(rhs)).1
+    }
+
+    fn pad(region: &Range2D, padding: f32) -> Range2D {
+        let pad_x = (region.x_range().end() - region.x_range().start()) * padding;
+        let pad_y = (region.y_range().end() - region.y_range().start()) * padding;
+
+        Range2D::new(
+            (region.x_range().start() - pad_x)..=(region.x_range().end() + pad_x),
+            (region.y_range().start() - pad_y)..=(region.y_range().end() + pad_y)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AutoFrame;
+
+    // 1000 points on the diagonal of [0..1]^2 and one far away.
+    fn samples_with_outlier() -> Vec<f32> {
+        let mut data: Vec<f32> = (0..1000)
+            .flat_map(|idx| { let v = idx as f32 / 999.0; [v, v] })
+            .collect();
+        data.extend_from_slice(&[1e6, -1e6, f32::NAN, f32::INFINITY]);
+        data
+    }
+
+    #[test]
+    fn test_outlier_is_ignored() {
+        let frame = AutoFrame { lower_percentile: 0.01, upper_percentile: 0.99, padding: 0.0, lock_aspect: true };
+        let region = frame.region(&samples_with_outlier());
+
+        assert!(*region.x_range().start() > 0.0);
+        assert!(*region.x_range().end() < 1.0);
+        assert!(*region.y_range().start() > 0.0);
+        assert!(*region.y_range().end() < 1.0);
+    }
+
+    #[test]
+    fn test_padding_and_aspect() {
+        let data = [0.0, 0.0, 2.0, 1.0];
+        let frame = AutoFrame { lower_percentile: 0.0, upper_percentile: 1.0, padding: 0.5, lock_aspect: true };
+
+        // [-1..3] x [-0.5..1.5] into 100x100.
+        let camera = frame.frame(&data, 100, 100);
+        assert!((camera.scale - 25.0).abs() < 1e-2);
+        assert_eq!((camera.center_x, camera.center_y), (1.0, 0.5));
+        assert_eq!((camera.width, camera.height), (100, 100));
+
+        let unlocked = AutoFrame { lock_aspect: false, ..frame };
+        let camera = unlocked.frame(&data, 100, 100);
+        assert_eq!((camera.width, camera.height), (100, 50));
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use crate::ds::array_2d::Index2D;
+use crate::statistics::plane::Range2D;
+
+/// Maps plane coordinates to pixels.
+///
+/// Plane is translated so that `center` is in the middle of the image,
+/// rotated by `-rotation` (so the view turns counterclockwise by `rotation` radians),
+/// and scaled by `scale * 2^zoom` pixels per plane unit.
+/// Pixels are square, i.e. aspect ratio is `width / height`.
+///
+/// Pixel (i, j) covers [i..i+1) x [j..j+1) in the continuous grid coordinates.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Camera {
+    pub(crate) center_x: f32,
+    pub(crate) center_y: f32,
+    /// Pixels per plane unit at zoom 0.
+    pub(crate) scale: f32,
+    /// Log2 magnification on top of `scale`.
+    pub(crate) zoom: f32,
+    /// Radians.
+    pub(crate) rotation: f32,
+    pub(crate) width: usize,
+    pub(crate) height: usize
+}
+
+impl Camera {
+    // pixels are half-open, so a region fit edge to edge would drop the samples on its max edges.
+    const FIT_MARGIN: f32 = 1e-4;
+
+    pub(crate) fn new(center_x: f32, center_y: f32, scale: f32, rotation: f32, width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0);
+        Self { center_x, center_y, scale, zoom: 0.0, rotation, width, height }
+    }
+
+    /// Smallest view (no rotation, zoom 0), which fits the whole region, max edges included.
+    pub(crate) fn fit(region: &Range2D, width: usize, height: usize) -> Self {
+        let len_x = (region.x_range().end() - region.x_range().start()).max(f32::EPSILON);
+        let len_y = (region.y_range().end() - region.y_range().start()).max(f32::EPSILON);
+
+        Self::new(
+            (region.x_range().start() + region.x_range().end()) / 2.0,
+            (region.y_range().start() + region.y_range().end()) / 2.0,
+            (width as f32 / len_x).min(height as f32 / len_y) * (1.0 - Self::FIT_MARGIN),
+            0.0,
+            width,
+            height
+        )
+    }
+
+    pub(crate) fn with_zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// Same view, different output size. Horizontal extent of the view is preserved,
+    /// so a thumbnail and its HD version frame the same thing, if aspect ratios match.
+    pub(crate) fn resized(&self, width: usize, height: usize) -> Self {
+        let mut res = self.clone();
+        res.scale = self.scale * width as f32 / self.width as f32;
+        res.width = width;
+        res.height = height;
+        res
+    }
+
+    pub(crate) fn pixels_per_unit(&self) -> f32 { self.scale * self.zoom.exp2() }
+
+    /// Continuous grid coordinates of a plane point, `None` if it's out of view.
+    pub(crate) fn to_grid(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let (sin, cos) = (-self.rotation).sin_cos();
+        let dx = x - self.center_x;
+        let dy = y - self.center_y;
+        let ppu = self.pixels_per_unit();
+
+        let grid_x = (dx * cos - dy * sin) * ppu + self.width as f32 / 2.0;
+        let grid_y = (dx * sin + dy * cos) * ppu + self.height as f32 / 2.0;
+
+        // NaN fails both comparisons.
+        let in_view = grid_x >= 0.0 && grid_x < self.width as f32 && grid_y >= 0.0 && grid_y < self.height as f32;
+
+        if in_view { Some((grid_x, grid_y)) } else { None }
+    }
+
+    pub(crate) fn bin(&self, x: f32, y: f32) -> Option<Index2D> {
+        self.to_grid(x, y)
+            .map(|(grid_x, grid_y)| Index2D::from(grid_x as usize, grid_y as usize))
+    }
+
+    /// Axis aligned bounding box of the view on the plane.
+    pub(crate) fn view_region(&self) -> Range2D {
+        let ppu = self.pixels_per_unit();
+        let half_w = self.width as f32 / 2.0 / ppu;
+        let half_h = self.height as f32 / 2.0 / ppu;
+        let (sin, cos) = self.rotation.sin_cos();
+
+        let ext_x = (half_w * cos).abs() + (half_h * sin).abs();
+        let ext_y = (half_w * sin).abs() + (half_h * cos).abs();
+
+        Range2D::new(
+            (self.center_x - ext_x)..=(self.center_x + ext_x),
+            (self.center_y - ext_y)..=(self.center_y + ext_y)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use crate::ds::array_2d::Index2D;
+    use crate::statistics::plane::Range2D;
+    use super::Camera;
+
+    #[test]
+    fn test_fit_and_bin() {
+        let camera = Camera::fit(&Range2D::new(0.0..=2.0, 0.0..=1.0), 4, 2);
+
+        assert!((camera.scale - 2.0).abs() < 1e-3);
+        assert_eq!(camera.bin(0.0, 0.0), Some(Index2D::from(0, 0)));
+        assert_eq!(camera.bin(1.9, 0.9), Some(Index2D::from(3, 1)));
+        assert_eq!(camera.bin(2.01, 0.5), None);
+        assert_eq!(camera.bin(-0.1, 0.5), None);
+        assert_eq!(camera.bin(f32::NAN, 0.5), None);
+    }
+
+    #[test]
+    fn test_fit_keeps_max_edges() {
+        let region = Range2D::new(-1.5..=2.5, 0.3..=1.7);
+
+        for (width, height) in [(4, 2), (256, 256), (1920, 1080), (1, 1)] {
+            let camera = Camera::fit(&region, width, height);
+            assert_eq!(camera.bin(2.5, 1.7).map(|index| index.x()), Some(width - 1));
+            assert!(camera.bin(-1.5, 0.3).is_some());
+            assert!(camera.bin(2.5, 0.3).is_some());
+            assert!(camera.bin(-1.5, 1.7).is_some());
+        }
+    }
+
+    #[test]
+    fn test_zoom_and_rotation() {
+        let camera = Camera::new(0.0, 0.0, 1.0, 0.0, 4, 4).with_zoom(1.0);
+        // 2 pixels per unit, view is [-1..1)^2.
+        assert_eq!(camera.bin(0.9, 0.0), Some(Index2D::from(3, 2)));
+        assert_eq!(camera.bin(1.1, 0.0), None);
+
+        // view turned by 90 degrees: plane +y is now grid +x.
+        let rotated = Camera::new(0.0, 0.0, 1.0, FRAC_PI_2, 4, 4).with_zoom(1.0);
+        let (grid_x, grid_y) = rotated.to_grid(0.0, 0.9).unwrap();
+        assert!((grid_x - 3.8).abs() < 1e-5);
+        assert!((grid_y - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_resized_keeps_framing() {
+        let thumb = Camera::fit(&Range2D::new(-1.0..=1.0, -1.0..=1.0), 64, 64);
+        let hd = thumb.resized(1024, 1024);
+
+        let region = hd.view_region();
+        assert_eq!(region.x_range(), thumb.view_region().x_range());
+        assert_eq!(hd.bin(0.0, 0.0), Some(Index2D::from(512, 512)));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let camera = Camera::new(0.5, -0.25, 128.0, 0.3, 256, 128).with_zoom(1.5);
+        let json = serde_json::to_string(&camera).unwrap();
+        let parsed: Camera = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(camera, parsed);
+    }
+}
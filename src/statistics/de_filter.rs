@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::ds::array_2d::Index2D;
+use crate::ds::color_histogram::ColorHistogram;
+
+/// FLAM3's density estimation filter: a post-pass over the accumulated histogram.
+///
+/// Each cell is spread with an Epanechnikov kernel of radius `max_radius / hits^curve`,
+/// (but no less than `min_radius`), so sparse regions are smoothed out heavily,
+/// and dense ones keep their detail. Unlike `DensityEstimator2D::kde_adapt`
+/// this needs only the histogram, not the samples.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DensityEstimationFilter {
+    /// In pixels.
+    pub(crate) min_radius: f32,
+    pub(crate) max_radius: f32,
+    /// How fast the radius shrinks with hit count.
+    pub(crate) curve: f32
+}
+
+impl Default for DensityEstimationFilter {
+    /// flam3 defaults.
+    fn default() -> Self {
+        Self { min_radius: 0.0, max_radius: 9.0, curve: 0.4 }
+    }
+}
+
+type Kernel = Vec<(isize, isize, f32)>;
+
+impl DensityEstimationFilter {
+    // radii are quantized to this many steps per pixel, so that kernels can be reused.
+    const RADIUS_STEPS: f32 = 4.0;
+
+    pub(crate) fn radius(&self, hits: f32) -> f32 {
+        (self.max_radius / hits.max(1.0).powf(self.curve)).max(self.min_radius)
+    }
+
+    /// Same filter for a histogram `factor` times larger on each axis, i.e. an oversampled one.
+    pub(crate) fn scaled(&self, factor: usize) -> Self {
+        let factor = factor as f32;
+        Self { min_radius: self.min_radius * factor, max_radius: self.max_radius * factor, curve: self.curve }
+    }
+
+    /// Filtered histogram of the same size, total hits and color sums are preserved
+    /// (save for what is spread over the edges).
+    pub(crate) fn apply(&self, hist: &ColorHistogram) -> ColorHistogram {
+        assert!(self.min_radius <= self.max_radius);
+
+        let mut res = hist.blank_copy();
+        let mut kernels = HashMap::<u32, Kernel>::new();
+        let width = hist.width() as isize;
+        let height = hist.height() as isize;
+
+        for x in 0..hist.width() {
+            for y in 0..hist.height() {
+                let index = Index2D::from(x, y);
+                let hits = hist.hits(index);
+
+                if hits <= 0.0 {
+                    continue;
+                }
+
+                let step = (self.radius(hits) * Self::RADIUS_STEPS).round() as u32;
+                let kernel = kernels
+                    .entry(step)
+                    .or_insert_with(|| Self::kernel(step as f32 / Self::RADIUS_STEPS));
+                let color_sum = hist.color_sum(index);
+
+                for (dx, dy, weight) in kernel.iter() {
+                    let (kx, ky) = (x as isize + dx, y as isize + dy);
+
+                    if kx < 0 || ky < 0 || kx >= width || ky >= height {
+                        continue;
+                    }
+
+                    res.splat(Index2D::from(kx as usize, ky as usize), hits * weight, color_sum * weight);
+                }
+            }
+        }
+
+        res
+    }
+
+    // normalized, radius below half a pixel is just the cell itself.
+    fn kernel(radius: f32) -> Kernel {
+        if radius < 0.5 {
+            return vec![(0, 0, 1.0)];
+        }
+
+        let extent = radius.ceil() as isize;
+        let mut kernel: Kernel = Vec::new();
+
+        for dx in -extent..=extent {
+            for dy in -extent..=extent {
+                let u_sq = ((dx * dx + dy * dy) as f32) / (radius * radius);
+                if u_sq < 1.0 {
+                    kernel.push((dx, dy, 1.0 - u_sq));
+                }
+            }
+        }
+
+        let sum: f32 = kernel.iter().map(|(_, _, weight)| weight).sum();
+        kernel.iter_mut().for_each(|(_, _, weight)| *weight /= sum);
+
+        kernel
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::array_2d::Index2D;
+    use crate::ds::color_histogram::ColorHistogram;
+    use super::DensityEstimationFilter;
+
+    #[test]
+    fn test_radius_shrinks_with_hits() {
+        let filter = DensityEstimationFilter { min_radius: 1.0, max_radius: 8.0, curve: 0.5 };
+
+        assert_eq!(filter.radius(1.0), 8.0);
+        assert_eq!(filter.radius(16.0), 2.0);
+        assert_eq!(filter.radius(10_000.0), 1.0);
+    }
+
+    #[test]
+    fn test_sparse_cell_is_spread_dense_cell_is_kept() {
+        let filter = DensityEstimationFilter { min_radius: 0.0, max_radius: 2.0, curve: 0.5 };
+        let mut hist = ColorHistogram::new(9, 9);
+        let center = Index2D::from(4, 4);
+
+        hist.plot(center, 0.5);
+        let filtered = filter.apply(&hist);
+
+        assert!(filtered.hits(center) < 1.0);
+        assert!(filtered.hits(Index2D::from(5, 4)) > 0.0);
+        assert!((filtered.color(Index2D::from(5, 4)) - 0.5).abs() < 1e-5);
+
+        let total: f32 = (0..9).flat_map(|x| (0..9).map(move |y| Index2D::from(x, y)))
+            .map(|index| filtered.hits(index))
+            .sum();
+        assert!((total - 1.0).abs() < 1e-5);
+
+        // radius 2 / sqrt(100) is below half a pixel.
+        for _ in 0..99 {
+            hist.plot(center, 0.5);
+        }
+        let filtered = filter.apply(&hist);
+        assert_eq!(filtered.hits(center), 100.0);
+        assert_eq!(filtered.total_hits(), 100);
+    }
+}
@@ -1,17 +1,62 @@
 use std::ops::RangeInclusive;
 
 use crate::{statistics::samples::BivariateSample, ds::array_2d::{Index2D, Array2D}};
+use crate::ds::color_histogram::ColorHistogram;
+use crate::statistics::camera::Camera;
+use crate::statistics::oversampling::Oversampling;
 use crate::statistics::samples::Axis;
 use super::samples::RandomVec2D;
 use crate::util::remap;
 
 pub(crate) struct DensityEstimator2D {
-    samples: BivariateSample
+    samples: BivariateSample,
+    colors: Option<Vec<f32>>,
+    // `None` - the grid is fit to the region of the samples.
+    camera: Option<Camera>
 }
 
 impl DensityEstimator2D {
     pub(crate) fn new(data: &[f32]) -> Self {
-        Self { samples: BivariateSample::from(data) }
+        Self { samples: BivariateSample::from(data), colors: None, camera: None }
+    }
+
+    /// `colors[i]` is the color coordinate of the i-th sample.
+    pub(crate) fn new_colored(data: &[f32], colors: Vec<f32>) -> Self {
+        assert_eq!(data.len(), 2 * colors.len());
+        Self { samples: BivariateSample::from(data), colors: Some(colors), camera: None }
+    }
+
+    /// Samples are framed by the camera (resized to the requested grid), instead of the auto-fit.
+    /// Samples out of view are discarded.
+    pub(crate) fn with_camera(mut self, camera: Camera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    fn grid_camera(&self, width: usize, height: usize) -> Option<Camera> {
+        self.camera.as_ref().map(|camera| camera.resized(width, height))
+    }
+
+    // Continuous grid coordinates in [0..width-1]x[0..height-1], rounding yields the bin.
+    // `camera` is the one from `grid_camera`.
+    fn to_grid(&self, camera: &Option<Camera>, sample: &RandomVec2D, width: usize, height: usize) -> Option<(f32, f32)> {
+        match camera {
+            Some(camera) => {
+                // camera's pixel i is [i..i+1), shift so that rounding lands in it.
+                camera
+                    .to_grid(sample[Axis::x], sample[Axis::y])
+                    .map(|(x, y)| ((x - 0.5).max(0.0), (y - 0.5).max(0.0)))
+            }
+            None => {
+                let grid_x_range = 0.0..=((width as f32) - 1.0);
+                let grid_y_range = 0.0..=((height as f32) - 1.0);
+
+                Some((
+                    remap(sample[Axis::x], self.samples.region().x_range(), &grid_x_range),
+                    remap(sample[Axis::y], self.samples.region().y_range(), &grid_y_range)
+                ))
+            }
+        }
     }
 }
 
@@ -20,13 +65,10 @@ impl DensityEstimator2D {
     // `height` - number of Y-axis bins [0..height-1]
     pub(crate) fn histogram(&self, width: usize, height: usize) -> Array2D {
         let mut res = Array2D::new(width, height);
-
-        let grid_x_range = 0.0..=((width as f32) - 1.0);
-        let grid_y_range = 0.0..=((height as f32) - 1.0);
+        let camera = self.grid_camera(width, height);
 
         for sample in self.samples.iter() {
-            let remapped_x = remap(sample[Axis::x], self.samples.region().x_range(), &grid_x_range);
-            let remapped_y = remap(sample[Axis::y], self.samples.region().y_range(), &grid_y_range);
+            let Some((remapped_x, remapped_y)) = self.to_grid(&camera, sample, width, height) else { continue };
 
             let index = Index2D::from(remapped_x.round() as usize, remapped_y.round() as usize);
 
@@ -36,6 +78,27 @@ impl DensityEstimator2D {
 
         res
     }
+
+    /// Same binning as `histogram`, but keeps raw hit counts and color sums.
+    pub(crate) fn histogram_colored(&self, width: usize, height: usize) -> ColorHistogram {
+        let colors = self.colors.as_ref().expect("Samples have no colors!");
+        let mut res = ColorHistogram::new(width, height);
+        let camera = self.grid_camera(width, height);
+
+        for (sample, color) in self.samples.iter().zip(colors.iter()) {
+            let Some((remapped_x, remapped_y)) = self.to_grid(&camera, sample, width, height) else { continue };
+
+            res.plot(Index2D::from(remapped_x.round() as usize, remapped_y.round() as usize), *color);
+        }
+
+        res
+    }
+
+    /// `histogram_colored`, accumulated into a larger grid and downsampled to `width` x `height`.
+    pub(crate) fn histogram_oversampled(&self, width: usize, height: usize, oversampling: &Oversampling) -> ColorHistogram {
+        let (buffer_width, buffer_height) = oversampling.buffer_size(width, height);
+        oversampling.downsample(&self.histogram_colored(buffer_width, buffer_height))
+    }
 }
 
 impl DensityEstimator2D {
@@ -72,6 +135,7 @@ impl DensityEstimator2D {
 
         let grid_x_range = 0.0..=((width as f32) - 1.0);
         let grid_y_range = 0.0..=((height as f32) - 1.0);
+        let camera = self.grid_camera(width, height);
 
         let mut coords = vec![Index2D::from(0, 0); width * height];
 
@@ -85,8 +149,7 @@ impl DensityEstimator2D {
         */
 
         for sample in self.samples.iter() {
-            let remapped_x = remap(sample[Axis::x], self.samples.region().x_range(), &grid_x_range);
-            let remapped_y = remap(sample[Axis::y], self.samples.region().y_range(), &grid_y_range);
+            let Some((remapped_x, remapped_y)) = self.to_grid(&camera, sample, width, height) else { continue };
             let remapped_sample = RandomVec2D::from(remapped_x, remapped_y);
             let h = get_h(&remapped_sample);
             let cell_count = Self::cells_to_inc_around(
@@ -147,6 +210,8 @@ impl DensityEstimator2D {
 mod tests {
     use crate::{statistics::samples::RandomVec2D, ds::array_2d::Index2D};
 
+    use crate::statistics::camera::Camera;
+    use crate::statistics::plane::Range2D;
     use super::DensityEstimator2D;
 
     #[test]
@@ -183,5 +248,17 @@ mod tests {
         assert_eq!(res[0..count], expected);
     }
 
+    #[test]
+    fn test_histogram_with_camera_discards_out_of_view() {
+        // one outlier would stretch the auto-fit grid, camera ignores it.
+        let data = [0.1, 0.1, 0.9, 0.9, 100.0, 100.0];
+        let camera = Camera::fit(&Range2D::new(0.0..=1.0, 0.0..=1.0), 2, 2);
+        let density = DensityEstimator2D::new(&data).with_camera(camera).histogram(2, 2);
+
+        assert!(density[Index2D::from(0, 0)] > 0.0);
+        assert!(density[Index2D::from(1, 1)] > 0.0);
+        assert_eq!(density.non_zero_count(), 2);
+    }
+
     // - TODO: test. something that should integrate to unity, and be closer to distribution then original.
 }
\ No newline at end of file
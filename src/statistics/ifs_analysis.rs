@@ -0,0 +1,250 @@
+use crate::chaos_game::AffineTransformProvider;
+use crate::ds::affine_mat::AffineMat;
+use crate::ds::ifs_transform::IfsTransform;
+use crate::ds::point::Point;
+use crate::modnar::Modnar;
+use crate::statistics::plane::Range2D;
+
+/// What can be told about an IFS w/o rendering it.
+///
+/// Lipschitz constants, bounds and dimension are of the affine part (post-affine after pre-affine, mutators aside).
+/// Lyapunov exponent is of the whole thing, mutators included.
+#[derive(Clone, Debug)]
+pub(crate) struct IfsAnalysis {
+    /// Largest singular value of each transform's affine part, the most it stretches any distance by.
+    pub(crate) lipschitz: Vec<f32>,
+    /// Box, which contains the attractor of the affine part. None - the affine part isn't contractive.
+    pub(crate) bounds: Option<Range2D>,
+    /// s, s.t. sum of Lipschitz constants to the power of s is 1 (Moran equation).
+    /// Exact for similarities w/o overlaps, an upper bound estimate otherwise. None - not contractive.
+    pub(crate) similarity_dimension: Option<f32>,
+    /// Average log of how much the chaos game stretches nearby points apart per step.
+    /// Negative - points converge onto an attractor.
+    pub(crate) lyapunov_exponent: f32
+}
+
+/// Search filter over `IfsAnalysis`.
+#[derive(Clone, Debug)]
+pub(crate) struct AnalysisFilter {
+    pub(crate) max_lyapunov_exponent: f32,
+    /// Of the bounds, when there are some.
+    pub(crate) max_extent: f32,
+    /// Below 1 - dust, too sparse to look at.
+    pub(crate) min_similarity_dimension: f32
+}
+
+impl Default for AnalysisFilter {
+    fn default() -> Self {
+        Self { max_lyapunov_exponent: -0.05, max_extent: 1e3, min_similarity_dimension: 1.0 }
+    }
+}
+
+impl IfsAnalysis {
+    // points are separated by this much, relative to the scale of the attractor.
+    const SEPARATION: f32 = 1e-3;
+    const BOX_ITERATIONS: usize = 32;
+
+    /// `iterations` - of the Lyapunov exponent estimate (chaos game steps).
+    pub(crate) fn analyze(aff_t_provider: &impl AffineTransformProvider, iterations: u32, seed: u64) -> Self {
+        let affine: Vec<AffineMat> = aff_t_provider
+            .transforms()
+            .iter()
+            .map(Self::affine_part)
+            .collect();
+
+        let lipschitz: Vec<f32> = affine.iter().map(|mat| mat.singular_values().0).collect();
+        let contractive = lipschitz.iter().all(|l| *l < 1.0);
+
+        Self {
+            bounds: if contractive { Some(Self::bounds(&affine, &lipschitz)) } else { None },
+            similarity_dimension: if contractive { Some(Self::similarity_dimension(&lipschitz)) } else { None },
+            lyapunov_exponent: Self::lyapunov_exponent(aff_t_provider, iterations, seed),
+            lipschitz
+        }
+    }
+
+    pub(crate) fn is_contractive(&self) -> bool {
+        self.lipschitz.iter().all(|l| *l < 1.0)
+    }
+
+    pub(crate) fn passes(&self, filter: &AnalysisFilter) -> bool {
+        let small_enough = self.bounds.as_ref().is_none_or(|bounds| {
+            let extent_x = bounds.x_range().end() - bounds.x_range().start();
+            let extent_y = bounds.y_range().end() - bounds.y_range().start();
+            extent_x.max(extent_y) <= filter.max_extent
+        });
+        let dense_enough = self.similarity_dimension.is_none_or(|dim| dim >= filter.min_similarity_dimension);
+
+        self.lyapunov_exponent < filter.max_lyapunov_exponent && small_enough && dense_enough
+    }
+
+    fn affine_part(transform: &IfsTransform) -> AffineMat {
+        match &transform.post {
+            Some(post) => post.compose(&transform.mat),
+            None => transform.mat.clone()
+        }
+    }
+
+    // Ball around the origin, which each transform maps into itself, then refined by
+    // pushing its box through the transforms (Hutchinson operator on boxes): each step still contains the attractor.
+    fn bounds(affine: &[AffineMat], lipschitz: &[f32]) -> Range2D {
+        let radius = affine
+            .iter()
+            .zip(lipschitz)
+            .map(|(mat, l)| mat.e.hypot(mat.f) / (1.0 - l))
+            .fold(0.0f32, f32::max);
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (-radius, -radius, radius, radius);
+
+        for _ in 0..Self::BOX_ITERATIONS {
+            let corners = [(min_x, min_y), (min_x, max_y), (max_x, min_y), (max_x, max_y)];
+            let images: Vec<(f32, f32)> = affine
+                .iter()
+                .flat_map(|mat| corners.iter().map(move |(x, y)| mat.apply(*x, *y)))
+                .collect();
+
+            // both contain the attractor, and so does their intersection.
+            min_x = images.iter().map(|p| p.0).fold(f32::MAX, f32::min).max(-radius);
+            min_y = images.iter().map(|p| p.1).fold(f32::MAX, f32::min).max(-radius);
+            max_x = images.iter().map(|p| p.0).fold(f32::MIN, f32::max).min(radius);
+            max_y = images.iter().map(|p| p.1).fold(f32::MIN, f32::max).min(radius);
+        }
+
+        Range2D::new(min_x..=max_x, min_y..=max_y)
+    }
+
+    // sum(l^s) is decreasing in s, so bisection.
+    fn similarity_dimension(lipschitz: &[f32]) -> f32 {
+        let moran = |s: f32| lipschitz.iter().filter(|l| **l > 0.0).map(|l| l.powf(s)).sum::<f32>();
+
+        if moran(0.0) <= 1.0 {
+            return 0.0;
+        }
+
+        let mut hi = 1.0;
+        while moran(hi) > 1.0 && hi < 1e3 {
+            hi *= 2.0;
+        }
+
+        let mut lo = 0.0;
+        for _ in 0..50 {
+            let mid = (lo + hi) / 2.0;
+            if moran(mid) > 1.0 { lo = mid; } else { hi = mid; }
+        }
+        (lo + hi) / 2.0
+    }
+
+    // Benettin: a point and its neighbour go through the same transforms (w. the same random draws),
+    // the gap is measured and renormalized each step.
+    fn lyapunov_exponent(aff_t_provider: &impl AffineTransformProvider, iterations: u32, seed: u64) -> f32 {
+        const FUSE: u32 = 20;
+
+        let selector = aff_t_provider.selector_or_new();
+        let transforms = aff_t_provider.transforms();
+        let mut rnd = Modnar::new_rng_seeded(seed);
+
+        let mut point = Point::new(rnd.gen_f32(), rnd.gen_f32());
+        let mut direction = (1.0f32, 0.0f32);
+        let mut prev = None;
+        let mut log_sum = 0.0f64;
+        let mut steps = 0u32;
+
+        for i in 1..=iterations + FUSE {
+            let idx = selector.pick(prev, rnd.gen_f32());
+            prev = Some(idx);
+            let transform = &transforms[idx];
+
+            let separation = Self::SEPARATION * point.x.hypot(point.y).max(1.0);
+            let neighbour = Point::new(point.x + separation * direction.0, point.y + separation * direction.1);
+
+            let mut neighbour_rnd = rnd.clone();
+            let next = transform.apply(&point, &mut rnd);
+            let next_neighbour = transform.apply(&neighbour, &mut neighbour_rnd);
+
+            if !(next.x.is_finite() && next.y.is_finite()) {
+                // blew up - restart, and count it as stretching.
+                point = Point::new(rnd.gen_f32(), rnd.gen_f32());
+                log_sum += 1.0;
+                steps += 1;
+                continue;
+            }
+
+            let (dx, dy) = (next_neighbour.x - next.x, next_neighbour.y - next.y);
+            let gap = dx.hypot(dy);
+            if gap.is_finite() && gap > 0.0 {
+                direction = (dx / gap, dy / gap);
+            }
+
+            if i > FUSE {
+                let stretch = if gap.is_finite() { gap / separation } else { f32::MAX };
+                log_sum += (stretch.max(1e-12) as f64).ln();
+                steps += 1;
+            }
+            point = next;
+        }
+
+        (log_sum / steps.max(1) as f64) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::aff_ifs::ChaosGamePreprocess;
+    use crate::ds::ifs_transform::IfsTransform;
+    use crate::mutators::MutatorConfig;
+    use super::{AnalysisFilter, IfsAnalysis};
+
+    fn gasket() -> Vec<IfsTransform> {
+        let mut ifs = vec![
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.33, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 0.33, 0.5, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.5), 0.33, 1.0, 0.5),
+        ];
+        ifs.prepare_for_chaos_game(false);
+        ifs
+    }
+
+    #[test]
+    fn test_gasket() {
+        let analysis = IfsAnalysis::analyze(&gasket(), 10_000, 42);
+
+        assert!(analysis.is_contractive());
+        assert!((analysis.similarity_dimension.unwrap() - 3f32.log2()).abs() < 1e-4);
+        assert!((analysis.lyapunov_exponent - 0.5f32.ln()).abs() < 1e-3);
+
+        // attractor is the unit triangle.
+        let bounds = analysis.bounds.clone().unwrap();
+        assert!(bounds.x_range().start().abs() < 1e-3 && (bounds.x_range().end() - 1.0).abs() < 1e-3);
+        assert!(bounds.y_range().start().abs() < 1e-3 && (bounds.y_range().end() - 1.0).abs() < 1e-3);
+        assert!(analysis.passes(&AnalysisFilter::default()));
+    }
+
+    #[test]
+    fn test_expanding() {
+        let mut ifs = gasket();
+        ifs[0].mat = AffineMat::scale(3.0, 3.0);
+        let analysis = IfsAnalysis::analyze(&ifs, 10_000, 42);
+
+        assert!(!analysis.is_contractive());
+        assert!(analysis.bounds.is_none() && analysis.similarity_dimension.is_none());
+        // log 3 + 2 * log 0.5 on average, still (barely) shrinking.
+        assert!((analysis.lyapunov_exponent - (3f32.ln() + 2.0 * 0.5f32.ln()) / 3.0).abs() < 0.05);
+
+        let mut ifs = gasket();
+        ifs.iter_mut().for_each(|transform| transform.mat = AffineMat::scale(2.0, 2.0));
+        let analysis = IfsAnalysis::analyze(&ifs, 10_000, 42);
+        assert!(analysis.lyapunov_exponent > 0.0);
+        assert!(!analysis.passes(&AnalysisFilter::default()));
+    }
+
+    #[test]
+    fn test_mutated() {
+        // w. mutators it's measured, not derived - swirl of the gasket still converges.
+        let mut ifs = gasket();
+        ifs.iter_mut().for_each(|transform| transform.mutators = vec![MutatorConfig::named(1.0, "swirl", &[]).unwrap()]);
+        let analysis = IfsAnalysis::analyze(&ifs, 10_000, 42);
+
+        assert!(analysis.lyapunov_exponent.is_finite() && analysis.lyapunov_exponent < 0.0);
+    }
+}
@@ -1,3 +1,8 @@
 pub(crate) mod grid_density;
 pub(crate) mod samples;
-mod plane;
+pub(crate) mod plane;
+pub(crate) mod camera;
+pub(crate) mod auto_frame;
+pub(crate) mod de_filter;
+pub(crate) mod oversampling;
+pub(crate) mod ifs_analysis;
@@ -0,0 +1,184 @@
+use std::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+use crate::ds::array_2d::Index2D;
+use crate::ds::color_histogram::ColorHistogram;
+
+/// Reconstruction filter for downsampling, see `Oversampling`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum ReconstructionFilter {
+    Box,
+    Gaussian,
+    /// B = C = 1/3.
+    Mitchell,
+    /// 3 lobes.
+    Lanczos
+}
+
+impl ReconstructionFilter {
+    /// Filter support, in output pixels (at radius 1).
+    fn support(&self) -> f32 {
+        match self {
+            Self::Box => 0.5,
+            Self::Gaussian => 1.5,
+            Self::Mitchell => 2.0,
+            Self::Lanczos => 3.0
+        }
+    }
+
+    fn weight(&self, t: f32) -> f32 {
+        let t = t.abs();
+
+        match self {
+            Self::Box => if t <= 0.5 { 1.0 } else { 0.0 },
+            // sigma = 0.5
+            Self::Gaussian => (-2.0 * t * t).exp(),
+            Self::Mitchell => {
+                const B: f32 = 1.0 / 3.0;
+                const C: f32 = 1.0 / 3.0;
+
+                if t < 1.0 {
+                    ((12.0 - 9.0 * B - 6.0 * C) * t * t * t + (-18.0 + 12.0 * B + 6.0 * C) * t * t + (6.0 - 2.0 * B)) / 6.0
+                } else if t < 2.0 {
+                    ((-B - 6.0 * C) * t * t * t + (6.0 * B + 30.0 * C) * t * t + (-12.0 * B - 48.0 * C) * t + (8.0 * B + 24.0 * C)) / 6.0
+                } else {
+                    0.0
+                }
+            },
+            Self::Lanczos => {
+                if t < f32::EPSILON {
+                    1.0
+                } else if t < 3.0 {
+                    3.0 * (PI * t).sin() * (PI * t / 3.0).sin() / (PI * PI * t * t)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Points are accumulated into a `factor` times larger (on each axis) histogram,
+/// which is then downsampled to the requested size with the reconstruction filter.
+/// Rounding each point into exactly one output pixel is what makes edges jagged.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Oversampling {
+    pub(crate) factor: usize,
+    pub(crate) filter: ReconstructionFilter,
+    /// Filter is stretched by this much, in output pixels.
+    pub(crate) radius: f32
+}
+
+impl Default for Oversampling {
+    fn default() -> Self {
+        Self { factor: 2, filter: ReconstructionFilter::Gaussian, radius: 1.0 }
+    }
+}
+
+impl Oversampling {
+    pub(crate) fn new(factor: usize, filter: ReconstructionFilter, radius: f32) -> Self {
+        assert!(factor > 0 && radius > 0.0);
+        Self { factor, filter, radius }
+    }
+
+    /// Size of the buffer to accumulate into.
+    pub(crate) fn buffer_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (width * self.factor, height * self.factor)
+    }
+
+    /// `hist` is `buffer_size` of the output. Total hits are preserved (save for the edges).
+    /// Mitchell and Lanczos have negative lobes, which can leave cells with negative hits,
+    /// tone mapping treats those as empty.
+    pub(crate) fn downsample(&self, hist: &ColorHistogram) -> ColorHistogram {
+        let width = hist.width() / self.factor;
+        let height = hist.height() / self.factor;
+        let kernel = self.kernel();
+        let mut res = hist.blank_resized(width, height);
+
+        for x in 0..width {
+            for y in 0..height {
+                let (mut hits, mut color_sum) = (0.0, 0.0);
+
+                for (dx, weight_x) in kernel.iter() {
+                    let Some(sx) = (x * self.factor).checked_add_signed(*dx).filter(|sx| *sx < hist.width()) else { continue };
+
+                    for (dy, weight_y) in kernel.iter() {
+                        let Some(sy) = (y * self.factor).checked_add_signed(*dy).filter(|sy| *sy < hist.height()) else { continue };
+
+                        let index = Index2D::from(sx, sy);
+                        hits += weight_x * weight_y * hist.hits(index);
+                        color_sum += weight_x * weight_y * hist.color_sum(index);
+                    }
+                }
+
+                res.splat(Index2D::from(x, y), hits, color_sum);
+            }
+        }
+
+        res
+    }
+
+    // 1D (the filter is separable), offsets are relative to the first buffer cell of the output pixel.
+    // Normalized so that each buffer cell contributes exactly 1 in total over all the output pixels
+    // (i.e. output cells hold sums, not averages). Offsets congruent mod `factor` belong to the same
+    // buffer cell, seen from different output pixels.
+    fn kernel(&self) -> Vec<(isize, f32)> {
+        let factor = self.factor as f32;
+        let extent = (self.filter.support() * self.radius * factor).ceil() as isize;
+
+        let mut kernel: Vec<(isize, f32)> = (-extent..(self.factor as isize + extent))
+            .map(|offset| {
+                // distance between cell center and output pixel center, in output pixels.
+                let dist = (offset as f32 + 0.5) / factor - 0.5;
+                (offset, self.filter.weight(dist / self.radius))
+            })
+            .filter(|(_, weight)| *weight != 0.0)
+            .collect();
+
+        let mut sums = vec![0.0; self.factor];
+        kernel.iter().for_each(|(offset, weight)| sums[offset.rem_euclid(self.factor as isize) as usize] += weight);
+        kernel.iter_mut().for_each(|(offset, weight)| *weight /= sums[offset.rem_euclid(self.factor as isize) as usize]);
+
+        kernel
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ds::array_2d::Index2D;
+    use crate::ds::color_histogram::ColorHistogram;
+    use super::{Oversampling, ReconstructionFilter};
+
+    #[test]
+    fn test_box_sums_blocks() {
+        let oversampling = Oversampling::new(2, ReconstructionFilter::Box, 1.0);
+        let mut hist = ColorHistogram::new(4, 2);
+
+        hist.plot(Index2D::from(0, 0), 0.0);
+        hist.plot(Index2D::from(1, 1), 1.0);
+        hist.plot(Index2D::from(3, 0), 1.0);
+
+        let res = oversampling.downsample(&hist);
+        assert_eq!((res.width(), res.height()), (2, 1));
+        assert_eq!(res.hits(Index2D::from(0, 0)), 2.0);
+        assert_eq!(res.color(Index2D::from(0, 0)), 0.5);
+        assert_eq!(res.hits(Index2D::from(1, 0)), 1.0);
+    }
+
+    #[test]
+    fn test_filters_preserve_total() {
+        for filter in [ReconstructionFilter::Gaussian, ReconstructionFilter::Mitchell, ReconstructionFilter::Lanczos] {
+            let oversampling = Oversampling::new(3, filter, 1.0);
+            let mut hist = ColorHistogram::new(30, 30);
+            // the center cell of output pixel (4, 5).
+            hist.plot(Index2D::from(13, 16), 1.0);
+
+            let res = oversampling.downsample(&hist);
+            let total: f32 = (0..10).flat_map(|x| (0..10).map(move |y| Index2D::from(x, y)))
+                .map(|index| res.hits(index))
+                .sum();
+
+            assert!((total - 1.0).abs() < 1e-4, "{:?}: {}", filter, total);
+            assert!(res.hits(Index2D::from(4, 5)) > 0.5);
+        }
+    }
+}
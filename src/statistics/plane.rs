@@ -2,6 +2,7 @@ use std::ops::RangeInclusive;
 
 type AxisRange = RangeInclusive<f32>;
 
+#[derive(Clone, Debug)]
 pub(crate) struct Range2D {
     x: AxisRange,
     y: AxisRange
@@ -18,4 +19,6 @@ impl Range2D {
     pub(crate) fn x_range(&self) -> &AxisRange { &self.x }
 
     pub(crate) fn y_range(&self) -> &AxisRange { &self.y }
-}
\ No newline at end of file
+
+    pub(crate) fn contains(&self, x: f32, y: f32) -> bool { self.x.contains(&x) && self.y.contains(&y) }
+}
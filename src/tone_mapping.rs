@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+/// FLAM3 tone mapping parameters.
+///
+/// 1. Log-density scaling: each cell (hits and accumulated color) is scaled by
+///    `k1 * ln(1 + hits * k2) / hits`, where `k1` depends on brightness and contrast,
+///    and `k2` normalizes hits by the average number of hits per pixel.
+///    This is what makes images with different number of iterations / sizes comparable.
+/// 2. Gamma, linear below `gamma_threshold` (otherwise low densities are amplified into noise).
+/// 3. Vibrancy: 1 - gamma is applied to the alpha only (saturated colors),
+///    0 - to each color channel separately (washed out colors).
+/// 4. Highlight power: how overexposed cells are desaturated towards white. Negative - off.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ToneMapping {
+    pub(crate) brightness: f32,
+    pub(crate) contrast: f32,
+    pub(crate) gamma: f32,
+    pub(crate) gamma_threshold: f32,
+    pub(crate) vibrancy: f32,
+    pub(crate) highlight_power: f32
+}
+
+impl Default for ToneMapping {
+    /// flam3 defaults.
+    fn default() -> Self {
+        Self {
+            brightness: 4.0,
+            contrast: 1.0,
+            gamma: 4.0,
+            gamma_threshold: 0.01,
+            vibrancy: 1.0,
+            highlight_power: -1.0
+        }
+    }
+}
+
+impl ToneMapping {
+    // flam3's PREFILTER_WHITE / WHITE_LEVEL, channels here are in [0..1] instead of [0..255].
+    const WHITE_LEVEL: f32 = 255.0;
+
+    /// Log-density scale factors (k1, k2) for a histogram with `total_hits` spread over `pixels` cells.
+    pub(crate) fn log_scale_factors(&self, total_hits: u64, pixels: usize) -> (f32, f32) {
+        let k1 = self.contrast * self.brightness * 268.0 / 256.0;
+        let k2 = pixels as f32 / (self.contrast * Self::WHITE_LEVEL * (total_hits.max(1) as f32));
+
+        (k1, k2)
+    }
+
+    /// Maps a single cell. `hits` - (possibly filtered) hit count, `rgb` - color of the cell, in [0..1].
+    /// Returns RGBA, in [0..1].
+    pub(crate) fn map(&self, hits: f32, rgb: [f32; 3], k1: f32, k2: f32) -> [f32; 4] {
+        if hits <= 0.0 {
+            return [0.0; 4];
+        }
+
+        // log-density: accumulated color is rgb * hits, scaled by ls.
+        let log_alpha = k1 * (1.0 + hits * k2).ln();
+        let acc = rgb.map(|channel| channel * log_alpha);
+
+        let g = 1.0 / self.gamma;
+        let alpha = self.calc_alpha(log_alpha, g);
+        let ls = if log_alpha > 0.0 { self.vibrancy * alpha / log_alpha } else { 0.0 };
+
+        let mut res = self.calc_newrgb(&acc, ls);
+        for channel in 0..3 {
+            res[channel] += (1.0 - self.vibrancy) * acc[channel].max(0.0).powf(g);
+            res[channel] = res[channel].clamp(0.0, 1.0);
+        }
+
+        [res[0], res[1], res[2], alpha.clamp(0.0, 1.0)]
+    }
+
+    // gamma, linearized below the threshold.
+    fn calc_alpha(&self, density: f32, g: f32) -> f32 {
+        let threshold = self.gamma_threshold;
+
+        if density > 0.0 && density < threshold {
+            let frac = density / threshold;
+            (1.0 - frac) * density * (threshold.powf(g) / threshold) + frac * density.powf(g)
+        } else {
+            density.max(0.0).powf(g)
+        }
+    }
+
+    fn calc_newrgb(&self, acc: &[f32; 3], ls: f32) -> [f32; 3] {
+        let max_c = acc[0].max(acc[1]).max(acc[2]);
+
+        if ls == 0.0 || max_c <= 0.0 {
+            return [0.0; 3];
+        }
+
+        if self.highlight_power < 0.0 || ls * max_c <= 1.0 {
+            return acc.map(|channel| ls * channel);
+        }
+
+        // overexposed: scale down to the white level, and desaturate by how much we scaled.
+        let new_ls = 1.0 / max_c;
+        let ls_ratio = (new_ls / ls).powf(self.highlight_power);
+        let scaled = acc.map(|channel| new_ls * channel);
+
+        let (h, s, v) = rgb_to_hsv(&scaled);
+        hsv_to_rgb(h, s * ls_ratio, v)
+    }
+}
+
+fn rgb_to_hsv(rgb: &[f32; 3]) -> (f32, f32, f32) {
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    let min = rgb[0].min(rgb[1]).min(rgb[2]);
+    let delta = max - min;
+
+    let s = if max > 0.0 { delta / max } else { 0.0 };
+    let h = if delta <= 0.0 {
+        0.0
+    } else if rgb[0] == max {
+        (rgb[1] - rgb[2]) / delta
+    } else if rgb[1] == max {
+        2.0 + (rgb[2] - rgb[0]) / delta
+    } else {
+        4.0 + (rgb[0] - rgb[1]) / delta
+    };
+
+    (h.rem_euclid(6.0), s, max)
+}
+
+// h in [0..6)
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let sector = h.floor();
+    let f = h - sector;
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - s * f);
+    let t = v * (1.0 - s * (1.0 - f));
+
+    match sector as u8 {
+        0 => [v, t, p],
+        1 => [q, v, p],
+        2 => [p, v, t],
+        3 => [p, q, v],
+        4 => [t, p, v],
+        _ => [v, p, q]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hsv_to_rgb, rgb_to_hsv, ToneMapping};
+
+    #[test]
+    fn test_empty_cell_is_transparent() {
+        let tm = ToneMapping::default();
+        assert_eq!(tm.map(0.0, [1.0, 1.0, 1.0], 1.0, 1.0), [0.0; 4]);
+    }
+
+    #[test]
+    fn test_alpha_grows_with_density() {
+        let tm = ToneMapping::default();
+        let (k1, k2) = tm.log_scale_factors(1_000_000, 100 * 100);
+
+        let sparse = tm.map(1.0, [1.0, 0.0, 0.0], k1, k2);
+        let dense = tm.map(1000.0, [1.0, 0.0, 0.0], k1, k2);
+
+        assert!(sparse[3] > 0.0);
+        assert!(sparse[3] < dense[3]);
+        assert!(dense[3] <= 1.0);
+        // vibrancy 1 keeps the hue.
+        assert_eq!(dense[1], 0.0);
+    }
+
+    #[test]
+    fn test_brightness() {
+        let dim = ToneMapping { brightness: 1.0, ..ToneMapping::default() };
+        let bright = ToneMapping { brightness: 8.0, ..ToneMapping::default() };
+
+        let (k1, k2) = dim.log_scale_factors(10_000, 100);
+        let (bk1, bk2) = bright.log_scale_factors(10_000, 100);
+
+        assert!(dim.map(10.0, [0.5; 3], k1, k2)[3] < bright.map(10.0, [0.5; 3], bk1, bk2)[3]);
+    }
+
+    #[test]
+    fn test_highlight_power_desaturates() {
+        let tm = ToneMapping { highlight_power: 1.0, brightness: 1000.0, ..ToneMapping::default() };
+        let (k1, k2) = tm.log_scale_factors(100, 1);
+
+        let rgba = tm.map(100.0, [1.0, 0.2, 0.2], k1, k2);
+        assert!(rgba[1] > 0.2);
+        assert!(rgba.iter().all(|channel| *channel <= 1.0));
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        let rgb = [0.8, 0.3, 0.1];
+        let (h, s, v) = rgb_to_hsv(&rgb);
+        let back = hsv_to_rgb(h, s, v);
+
+        for channel in 0..3 {
+            assert!((rgb[channel] - back[channel]).abs() < 1e-5);
+        }
+    }
+}
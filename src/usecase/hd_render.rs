@@ -1,31 +1,45 @@
 use std::collections::HashSet;
 use std::time::Instant;
+use image::RgbaImage;
 use crate::alg::combinations::Combinations;
-use crate::chaos_game::ChaosGame;
+use crate::chaos_game::{ChaosGame, RunStats};
 use crate::ds::aff_ifs::ChaosGamePreprocess;
-use crate::ds::array_2d::Array2D;
+use crate::ds::color_histogram::ColorHistogram;
+use crate::ds::flame_genome::FlameGenome;
 use crate::ds::ifs_transform::IfsTransform;
-use crate::ff_repository::mutator_description_service::MutatorDescriptionService;
 use crate::ff_repository::presets_repository::PresetsRepository;
 use crate::frac_render::RgbRenderer;
-use crate::mutators::{MutatorConfig, Mutators};
-use crate::statistics::grid_density::DensityEstimator2D;
+use crate::mutators::MutatorConfig;
+use crate::parallel_chaos_game::ParallelChaosGame;
+use crate::statistics::auto_frame::AutoFrame;
+use crate::statistics::camera::Camera;
 
-// Use color-steal for color mapping.
+// Palette can be color-stolen from reference artwork, see `PaletteRepository::steal`.
 pub(crate) struct HDRender;
 
 impl HDRender {
-    pub(crate) fn render(
+    // camera for the streaming histogram is fit to this many samples.
+    const PILOT_ITERATIONS: u32 = 100_000;
+
+    /// Genome of the `p_rank`-th combination of `draw_sz` presets, with default rendering settings
+    /// (tweak those in the genome). Rank doubles as the seed.
+    /// `mutators` - per transform, in the order of the combination (as the search has them, see
+    /// `StarshipEnterprise::roll_dice_mutators`), empty - the presets' own.
+    /// `camera` - the thumbnail's one, if there is one, so that HD render frames exactly the same thing.
+    ///
+    /// Symmetry changes the framing, so re-frame after setting it:
+    /// `genome.symmetry = Some(..); genome.camera = HDRender::pilot_camera(&genome);`
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn genome(
         draw_sz: u8,
         p_rank: u64,
-        m_rank: Option<u64>,
         presets_repo: &PresetsRepository,
-        mutator_repo: Option<&MutatorDescriptionService>,
+        mutators: &[Vec<MutatorConfig>],
         iterations: RenderIter,
-        use_kde: bool,
+        camera: Option<&Camera>,
         img_height: usize,
         img_width: usize
-    ) {
+    ) -> FlameGenome {
         let mut combinations = Combinations::new();
         let comb: HashSet<usize> = combinations
             .unrank(p_rank, presets_repo.flatted.len() as u8, draw_sz)
@@ -47,33 +61,70 @@ impl HDRender {
             .flatten()
             .collect();
 
+        // weights by determinant.
         ifs.prepare_for_chaos_game(true);
-        let iter = iterations as u32;
+        ifs.spread_colors();
+        if !mutators.is_empty() {
+            assert_eq!(mutators.len(), ifs.len(), "Mutators for {} transforms, got {}", ifs.len(), mutators.len());
+            ifs.iter_mut()
+                .zip(mutators)
+                .for_each(|(transform, mutators)| transform.mutators = mutators.clone());
+        }
+
+        // the camera is a placeholder, until there is a pilot run.
+        let mut genome = FlameGenome::new(ifs, Camera::new(0.0, 0.0, 1.0, 0.0, img_width, img_height), iterations as u32, p_rank);
+        genome.camera = match camera {
+            Some(camera) => camera.resized(img_width, img_height),
+            None => Self::pilot_camera(&genome)
+        };
+
+        genome
+    }
+
+    /// Camera of the genome's size, auto-framed to a short run of its chaos game.
+    pub(crate) fn pilot_camera(genome: &FlameGenome) -> Camera {
+        ChaosGame::new_seeded(genome.seed).pilot_camera(
+            &genome.prepared_ifs(),
+            Self::PILOT_ITERATIONS,
+            &AutoFrame::default(),
+            genome.camera.width,
+            genome.camera.height
+        )
+    }
+
+    pub(crate) fn render(genome: &FlameGenome) -> RgbaImage {
+        let (hist, _) = Self::histogram(genome);
+        Self::image(genome, &hist)
+    }
+
+    /// Histogram of the genome's output size (DE filtered and downsampled), and stats of the run.
+    /// Split from `render`, so that the search can look at it before it's tone mapped.
+    pub(crate) fn histogram(genome: &FlameGenome) -> (ColorHistogram, RunStats) {
         let instant = Instant::now();
-        let mut chaos_game = ChaosGame::new();
-        let samples = chaos_game
-            .run_chaos_game(&ifs, Some(&[
-                MutatorConfig::new(0.25, Mutators::Swirl),
-                //MutatorConfig::new(0.25, Mutators::Bent),
-                //MutatorConfig::new(0.25, Mutators::Julian { power: 5.0, dist: 0.31 }),
-                //MutatorConfig::new(0.25, Mutators::RadianBlur { angle: 1.27, v36: -5.5 })
-            ]), iter);
-        let density: Array2D;
-
-        if !use_kde {
-            density = DensityEstimator2D::new(&samples).histogram(img_height, img_width);
-        } else {
-            density = DensityEstimator2D::new(&samples).kde_adapt(img_height, img_width);
+        let ifs = genome.prepared_ifs();
+        let (buffer_width, buffer_height) = genome.oversampling.buffer_size(genome.camera.width, genome.camera.height);
+
+        let (mut hist, stats) = ParallelChaosGame::new(genome.seed, 0).run_chaos_game_streaming(
+            &ifs,
+            genome.iterations,
+            &genome.camera.resized(buffer_width, buffer_height)
+        );
+
+        // smooths out sparse regions, w/o keeping the samples around (like kde did).
+        // Runs on the oversampled buffer, as in flam3.
+        if let Some(de_filter) = &genome.de_filter {
+            hist = de_filter.scaled(genome.oversampling.factor).apply(&hist);
         }
+        let hist = genome.oversampling.downsample(&hist);
 
-        println!("Compute density in {}", instant.elapsed().as_secs_f32());
+        println!("Compute density in {}, bad values ratio {}", instant.elapsed().as_secs_f32(), stats.bad_values_ratio());
 
-        let render_method = if use_kde { "kde" } else { "hist" };
-        let num_of_iter = format!("{iter:e}");
+        (hist, stats)
+    }
 
-        let img = RgbRenderer::img_bw_simple(&density);
-        // 97074 is the rank of that particular permutation.
-        img.save(&format!("C:\\sizzling_hd_renders\\{draw_sz}.{p_rank}.4.3.{render_method}.{num_of_iter}.png")).unwrap();
+    /// `hist` - as returned by `histogram`.
+    pub(crate) fn image(genome: &FlameGenome, hist: &ColorHistogram) -> RgbaImage {
+        RgbRenderer::img_tone_mapped(hist, &genome.palette, &genome.tone_mapping)
     }
 }
 
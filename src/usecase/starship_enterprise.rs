@@ -1,52 +1,95 @@
 use std::collections::HashSet;
-use std::time::Instant;
+use image::RgbaImage;
 use crate::alg::big_range_random_cursor::BigRangeRandomCursor;
 use crate::alg::combinations::Combinations;
 use crate::chaos_game::ChaosGame;
 use crate::ds::aff_ifs::ChaosGamePreprocess;
+use crate::ds::flame_genome::FlameGenome;
 use crate::ds::ifs_transform::IfsTransform;
+use crate::ds::symmetry::Symmetry;
+use crate::ff_repository::genome_repository::GenomeRepository;
 use crate::ff_repository::presets_repository::PresetsRepository;
-use crate::mutators::Mutators;
+use crate::mutators::MutatorConfig;
+use crate::mutators::param_sampler::{ParamDistribution, ParamSampler};
 use crate::ff_repository::mutator_description_service::{MutatorDescription, MutatorDescriptionService};
-use crate::frac_render::RgbRenderer;
-use crate::mutators::Mutators::{Arch, Bent, Blade, Blob, Blur, Bubble, Cosine, Cross, Curl, Cylinder, Diamond, Disc, Ex, Exponential, Eyefish, Fan, Fan2, Fisheye, Gaussian, Handkerchief, Heart, Horseshoe, Hyperbolic, Julia, Julian, Julias, Ngon, Noise, Pdj, Perspective, Pie, Polar, Popcorn, Power, RadianBlur, Rays, Rectangles, Rings, Rings2, Secant, Sinus, Spherical, Spiral, Square, Swirl, Tangent, Twintrian, Waves};
-use crate::statistics::grid_density::DensityEstimator2D;
+use crate::statistics::auto_frame::AutoFrame;
+use crate::statistics::camera::Camera;
+use crate::statistics::ifs_analysis::{AnalysisFilter, IfsAnalysis};
+use crate::usecase::hd_render::HDRender;
 
 // free search - gen and save images
 // randomly traverse the:
 // all ifs possibilities
 // all mutator combos.
+// all params of a mutator combo.
 // look for criterion
-// 1. Number of non-zero pixels.
+// 0. `IfsAnalysis` passes the filter (before rendering).
+// 1. Number of non-zero pixels (of the thumbnail, rendered as the genome says, see `HDRender::render`).
+// promising ones (that pass) are also tried w. symmetries.
 
 pub(crate) struct StarshipEnterprise<'a> {
     presets_repository: &'a PresetsRepository,
     mutators: &'a MutatorDescriptionService,
     mutators_range_cur: BigRangeRandomCursor,
     presets_range_cur: BigRangeRandomCursor,
-    chaos_game: ChaosGame,
-    combinations: Combinations
+    combinations: Combinations,
+    symmetries: Vec<Symmetry>,
+    filter: AnalysisFilter,
+    param_distribution: ParamDistribution
 }
 
 impl<'a> StarshipEnterprise<'a> {
+    const THUMBNAIL_ITERATIONS: u32 = 400_000;
+    const THUMBNAIL_SIZE: usize = 256;
+    const ANALYSIS_ITERATIONS: u32 = 10_000;
+    // degenerate genomes are rejected after this many, before the full run.
+    const PILOT_ITERATIONS: u32 = 20_000;
+    // more than that, and the chaos game keeps falling off the attractor.
+    const MAX_BAD_VALUES_RATIO: f32 = 0.05;
+    // presets per IFS.
+    const DRAW_SZ: u8 = 4;
+    // params are drawn this many times per requested image, at most.
+    const MAX_PARAM_DRAWS_PER_IMG: u64 = 100;
+
     pub(crate) fn new(
         presets: &'a PresetsRepository,
         mutators: &'a MutatorDescriptionService
     ) -> Self {
         let mut combinations = Combinations::new();
-        let total_presets_comp = combinations.combinations(presets.flatted.len() as u8, 4);
-        println!("Total combinations {total_presets_comp}");
+        let total_presets_comp = combinations.combinations(presets.flatted.len() as u8, Self::DRAW_SZ);
+        // one mutator per transform.
+        let total_mutators_comp = Combinations::tuples(mutators.as_ref().len(), Self::DRAW_SZ);
+        println!("Total combinations {total_presets_comp}, mutator configurations {total_mutators_comp}");
         Self {
             presets_repository: presets,
             mutators,
-            // - TODO: range over total number of combinations from n by k.
-            mutators_range_cur: BigRangeRandomCursor::new_clean(1..=mutators.as_ref().len() as u64),
+            mutators_range_cur: BigRangeRandomCursor::new_clean(1..=total_mutators_comp),
             presets_range_cur: BigRangeRandomCursor::new_clean(1..=total_presets_comp),
-            chaos_game: ChaosGame::new(),
-            combinations: Combinations::new()
+            combinations: Combinations::new(),
+            symmetries: Vec::new(),
+            filter: AnalysisFilter::default(),
+            param_distribution: ParamDistribution::default()
         }
     }
 
+    pub(crate) fn with_filter(mut self, filter: AnalysisFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Of the params in `roll_dice_params`.
+    pub(crate) fn with_param_distribution(mut self, distribution: ParamDistribution) -> Self {
+        self.param_distribution = distribution;
+        self
+    }
+
+    /// Each thumbnail that passes is also rendered w. each of the `symmetries`,
+    /// named `{name}.{symmetry}` (i.e. `144708.d6`).
+    pub(crate) fn with_symmetries(mut self, symmetries: Vec<Symmetry>) -> Self {
+        self.symmetries = symmetries;
+        self
+    }
+
     pub(crate) fn roll_dice_presets(
         &mut self,
         path_to_samples: &str,
@@ -55,129 +98,238 @@ impl<'a> StarshipEnterprise<'a> {
         let mut img_generated = 0u16;
         let mut discarded = 0u16;
         while img_generated < total_img {
-            if let Some(perm_rank) = self.presets_range_cur.next() {
-                //println!("Discarded {discarded}");
-                let comb: HashSet<usize> = self.combinations
-                    .unrank(perm_rank, self.presets_repository.flatted.len() as u8, 4)
-                    .into_iter()
-                    .map(|e| (e - 1) as usize)
-                    .collect();
-
-                let mut ifs: Vec<IfsTransform> = self.presets_repository
-                    .flatted
-                    .iter()
-                    .enumerate()
-                    .map(|e| {
-                        if comb.contains(&e.0) {
-                            Some(e.1.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .flatten()
-                    .collect();
-
-                ifs.prepare_for_chaos_game(true);
-
-                if self.chaos_game.run_convergence_test(&ifs, None) {
-
-                    let now = Instant::now();
-                    let samples = self
-                        .chaos_game
-                        .run_chaos_game(&ifs, None, 400_000);
-                    let density = DensityEstimator2D::new(&samples).histogram(256, 256);
-
-                    let elapsed = now.elapsed();
-                    println!("Compute density in {}", elapsed.as_secs_f32());
-
-                    let min_fill = 0.1 * 256.0 * 256.0 as f32;
-
-                    if (density.non_zero_count() as f32) < min_fill {
-                        discarded += 1;
-                        continue;
-                    }
-
-                    let img = RgbRenderer::img_bw_simple(&density);
-
-                    img.save(&format!("{path_to_samples}\\{perm_rank}.png")).unwrap();
-
-                    img_generated += 1;
-                    println!("Presets {img_generated} out of {total_img}");
+            let Some(perm_rank) = self.presets_range_cur.next() else { break };
+            //println!("Discarded {discarded}");
+            let ifs = self.ifs(perm_rank);
+
+            if self.thumbnails(ifs, path_to_samples, &format!("{perm_rank}"), perm_rank) {
+                img_generated += 1;
+                println!("Presets {img_generated} out of {total_img}");
+            } else {
+                discarded += 1;
+            }
+        }
+    }
+
+    /// Searches over mutators for a fixed presets combination (`perm_rank`):
+    /// each transform gets its own mutator (w. default params). Mutator configuration is a tuple
+    /// of mutator indicies, one per transform, and is ranked as such, see `Combinations::unrank_tuple`.
+    /// Thumbnails are named `{perm_rank}.{mutators_rank}`.
+    pub(crate) fn roll_dice_mutators(
+        &mut self,
+        path_to_samples: &str,
+        perm_rank: u64,
+        total_img: u16
+    ) {
+        let mut img_generated = 0u16;
+        while img_generated < total_img {
+            let Some(mutators_rank) = self.mutators_range_cur.next() else { break };
+
+            let mut ifs = self.ifs(perm_rank);
+            let mutators = self.mutators_by(mutators_rank);
+            ifs.iter_mut()
+                .zip(mutators)
+                .for_each(|(transform, mutator)| transform.mutators = vec![mutator]);
+
+            if self.thumbnails(ifs, path_to_samples, &format!("{perm_rank}.{mutators_rank}"), mutators_rank) {
+                img_generated += 1;
+                println!("Mutators {img_generated} out of {total_img}");
+            }
+        }
+    }
+
+    /// Searches over params for a fixed presets combination (`perm_rank`) and mutator configuration
+    /// (`mutators_rank`, see `roll_dice_mutators`): params are drawn within their bounds (see `mut_desc.json`)
+    /// along the param distribution, seeded by the draw number. Thumbnails are named `{perm_rank}.{mutators_rank}.p{draw}`,
+    /// their genomes hold the drawn params.
+    pub(crate) fn roll_dice_params(
+        &mut self,
+        path_to_samples: &str,
+        perm_rank: u64,
+        mutators_rank: u64,
+        total_img: u16
+    ) {
+        let descriptions = self.descriptions_by(mutators_rank);
+        let mut img_generated = 0u16;
+
+        for draw in 1..=total_img as u64 * Self::MAX_PARAM_DRAWS_PER_IMG {
+            if img_generated >= total_img {
+                break;
+            }
+
+            let mut sampler = ParamSampler::new(draw).with_distribution(self.param_distribution.clone());
+            let mut ifs = self.ifs(perm_rank);
+            ifs.iter_mut()
+                .zip(&descriptions)
+                .for_each(|(transform, desc)| {
+                    let mutator = desc
+                        .config(1.0, |param| sampler.sample(param))
+                        .expect("Unknown mutator!");
+                    transform.mutators = vec![mutator];
+                });
+
+            if self.thumbnails(ifs, path_to_samples, &format!("{perm_rank}.{mutators_rank}.p{draw}"), draw) {
+                img_generated += 1;
+                println!("Params {img_generated} out of {total_img}");
+            }
+        }
+    }
+
+    fn ifs(&mut self, perm_rank: u64) -> Vec<IfsTransform> {
+        let comb: HashSet<usize> = self.combinations
+            .unrank(perm_rank, self.presets_repository.flatted.len() as u8, Self::DRAW_SZ)
+            .into_iter()
+            .map(|e| (e - 1) as usize)
+            .collect();
+
+        let mut ifs: Vec<IfsTransform> = self.presets_repository
+            .flatted
+            .iter()
+            .enumerate()
+            .map(|e| {
+                if comb.contains(&e.0) {
+                    Some(e.1.clone())
                 } else {
-                    discarded += 1;
+                    None
                 }
+            })
+            .flatten()
+            .collect();
 
-                continue
-            }
+        ifs.prepare_for_chaos_game(true);
+        ifs
+    }
+
+    fn mutators_by(&self, mutators_rank: u64) -> Vec<MutatorConfig> {
+        self.descriptions_by(mutators_rank)
+            .into_iter()
+            .map(|desc| desc.config(1.0, |param| param.default_value).expect("Unknown mutator!"))
+            .collect()
+    }
+
+    fn descriptions_by(&self, mutators_rank: u64) -> Vec<&'a MutatorDescription> {
+        let descriptions = self.mutators.as_ref();
+
+        Combinations::unrank_tuple(mutators_rank, descriptions.len(), Self::DRAW_SZ)
+            .into_iter()
+            .map(|e| &descriptions[e - 1])
+            .collect()
+    }
+
+    // thumbnail, and if it passes - one per symmetry.
+    fn thumbnails(&mut self, ifs: Vec<IfsTransform>, path_to_samples: &str, name: &str, seed: u64) -> bool {
+        if !self.thumbnail(ifs.clone(), path_to_samples, name, seed, None) {
+            return false;
+        }
+
+        for symmetry in self.symmetries.clone() {
+            self.thumbnail(ifs.clone(), path_to_samples, &format!("{name}.{symmetry}"), seed, Some(symmetry));
         }
+
+        true
     }
-}
 
-impl MutatorDescription {
-    fn into(&self) -> Option<Mutators> {
-        match self.enum_id {
-            1 => Some(Sinus),
-            2 => Some(Spherical),
-            3 => Some(Swirl),
-            4 => Some(Horseshoe),
-            5 => Some(Polar),
-            6 => Some(Handkerchief),
-            7 => Some(Heart),
-            8 => Some(Disc),
-            9 => Some(Spiral),
-            10 => Some(Hyperbolic),
-            11 => Some(Diamond),
-            12 => Some(Ex),
-            13 => Some(Julia),
-            14 => Some(Bent),
-            15 => Some(Waves),
-            16 => Some(Fisheye),
-            17 => Some(Popcorn),
-            18 => Some(Exponential),
-            19 => Some(Power),
-            20 => Some(Cosine),
-            21 => Some(Rings),
-            22 => Some(Fan),
-            23 => Some(Blob { blob_h: self.param("blob_h"), blob_l: self.param("blob_l"), blob_waves: self.param("blob_waves") }),
-            24 => Some(Pdj { pdj_a: self.param("pdj_a"), pdj_b: self.param("pdj_b"), pdj_c: self.param("pdj_c"), pdj_d: self.param("pdj_d") }),
-            25 => Some(Fan2 { fx: self.param("fx"), fy: self.param("fy") }),
-            26 => Some(Rings2 { rings2_val: self.param("rings2_val") }),
-            27 => Some(Eyefish),
-            28 => Some(Bubble),
-            29 => Some(Cylinder),
-            30 => Some(Perspective { p1_angle: self.param("p1_angle"), p2_dist: self.param("p2_dist") }),
-            31 => Some(Noise),
-            32 => Some(Julian { power: self.param("power"), dist: self.param("dist") }),
-            33 => Some(Julias { power: self.param("power"), dist: self.param("dist") }),
-            34 => Some(Blur),
-            35 => Some(Gaussian),
-            36 => Some(RadianBlur { angle: self.param("angle"), v36: self.param("v36") }),
-            37 => Some(Pie { slices: self.param("slices"), rotation: self.param("rotation"), thickness: self.param("thickness") }),
-            38 => Some(Ngon { power: self.param("power"), sides: self.param("sides"), corners: self.param("corners"), circle: self.param("circle") }) ,
-            39 => Some(Curl { c1: self.param("c1"), c2: self.param("c2") }),
-            40 => Some(Rectangles { rect_x: self.param("rect_x"), rect_y: self.param("rect_y") }),
-            41 => Some(Arch { v41: self.param("v41") }),
-            42 => Some(Tangent),
-            43 => Some(Square),
-            44 => Some(Rays { v44: self.param("v44") }),
-            45 => Some(Blade { v45: self.param("v45") }),
-            46 => Some(Secant { v46: self.param("v46") }),
-            47 => Some(Twintrian { v47: self.param("v47") }),
-            48 => Some(Cross),
-            _ => None
+    // renders and saves the thumbnail (and its genome), if the IFS converges and fills enough of the image.
+    fn thumbnail(&mut self, mut ifs: Vec<IfsTransform>, path_to_samples: &str, name: &str, seed: u64, symmetry: Option<Symmetry>) -> bool {
+        // symmetry transforms are isometries, they don't change convergence.
+        if !IfsAnalysis::analyze(&ifs, Self::ANALYSIS_ITERATIONS, seed).passes(&self.filter) {
+            return false;
+        }
+
+        // The camera is a placeholder, until the pilot run is framed.
+        ifs.spread_colors();
+        let mut genome = FlameGenome::new(
+            ifs,
+            Camera::new(0.0, 0.0, 1.0, 0.0, Self::THUMBNAIL_SIZE, Self::THUMBNAIL_SIZE),
+            Self::THUMBNAIL_ITERATIONS,
+            seed
+        );
+        genome.symmetry = symmetry;
+
+        let Some(img) = Self::render_thumbnail(&mut genome) else { return false };
+
+        img.save(&format!("{path_to_samples}\\{name}.png")).unwrap();
+
+        GenomeRepository::save(&genome, &format!("{path_to_samples}\\{name}.json")).unwrap();
+
+        true
+    }
+
+    /// Frames the genome, and renders it the same way `HDRender::render` does,
+    /// so that the thumbnail can be re-rendered bit-for-bit from its saved genome.
+    /// None - degenerate, or doesn't fill enough of the image.
+    fn render_thumbnail(genome: &mut FlameGenome) -> Option<RgbaImage> {
+        let ifs = genome.prepared_ifs();
+        let mut chaos_game = ChaosGame::new_seeded(genome.seed);
+        let samples = chaos_game.run_chaos_game(&ifs, Self::PILOT_ITERATIONS);
+        // degenerate ones don't get to the full run.
+        if chaos_game.stats().bad_values_ratio() > Self::MAX_BAD_VALUES_RATIO {
+            return None;
+        }
+
+        genome.camera = AutoFrame::default().frame(&samples, genome.camera.width, genome.camera.height);
+
+        let (hist, stats) = HDRender::histogram(genome);
+        if stats.bad_values_ratio() > Self::MAX_BAD_VALUES_RATIO {
+            return None;
+        }
+
+        let min_fill = 0.1 * (hist.width() * hist.height()) as f32;
+
+        if (hist.non_zero_count() as f32) < min_fill {
+            return None;
         }
+
+        Some(HDRender::image(genome, &hist))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::ds::aff_ifs::ChaosGamePreprocess;
+    use crate::ds::affine_mat::AffineMat;
+    use crate::ds::flame_genome::FlameGenome;
+    use crate::ds::ifs_transform::IfsTransform;
+    use crate::ff_repository::genome_repository::GenomeRepository;
     use crate::ff_repository::mutator_description_service::MutatorDescriptionService;
+    use crate::mutators::MutatorConfig;
+    use crate::statistics::camera::Camera;
+    use crate::usecase::hd_render::HDRender;
+    use super::StarshipEnterprise;
+
+    #[test]
+    fn test_thumbnail_rerenders_from_genome() {
+        let mut ifs = vec![
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 0.33, 0.0, 0.5),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 0.33, 0.0, 0.5)
+                .with_mutators(vec![MutatorConfig::named(1.0, "swirl", &[]).unwrap()]),
+            IfsTransform::new(AffineMat::from(0.5, 0.0, 0.0, 0.5, 0.0, 0.5), 0.33, 0.0, 0.5),
+        ];
+        ifs.prepare_for_chaos_game(false);
+        ifs.spread_colors();
+        let mut genome = FlameGenome::new(ifs, Camera::new(0.0, 0.0, 1.0, 0.0, 64, 64), 50_000, 42);
+
+        let thumbnail = StarshipEnterprise::render_thumbnail(&mut genome).expect("Gasket fills the thumbnail!");
+
+        let dir = std::env::temp_dir().join("sizzling1_test_thumbnail");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (png, json) = (dir.join("42.png"), dir.join("42.json"));
+        thumbnail.save(&png).unwrap();
+        GenomeRepository::save(&genome, json.to_str().unwrap()).unwrap();
+
+        let saved = image::open(&png).unwrap().to_rgba8();
+        let rerendered = HDRender::render(&GenomeRepository::load(json.to_str().unwrap()).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(saved.dimensions(), (64, 64));
+        assert_eq!(saved.as_raw(), rerendered.as_raw());
+    }
 
     #[test]
     fn test_conversion() {
         let muts = MutatorDescriptionService::load("")
             .expect("DB cannot be loaded!");
 
-        muts.as_ref().into_iter().for_each(|desc| { desc.into().expect("Ooops!"); });
+        muts.as_ref().into_iter().for_each(|desc| { desc.config(1.0, |param| param.default_value).expect("Ooops!"); });
     }
 }
\ No newline at end of file
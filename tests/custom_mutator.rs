@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use sizzling1::{AffineMat, Modnar, Mutator, MutatorError, MutatorParam, MutatorRegistry, Point};
+
+// as a downstream crate would have it.
+#[derive(Debug)]
+struct Shift {
+    id: u8,
+    params: Vec<MutatorParam>
+}
+
+impl Mutator for Shift {
+    fn id(&self) -> u8 { self.id }
+
+    fn name(&self) -> &str { "downstream_shift" }
+
+    fn params(&self) -> &[MutatorParam] { &self.params }
+
+    fn is_rand(&self) -> bool { false }
+
+    fn requires_affine(&self) -> bool { true }
+
+    fn apply(&self, p: &Point, mat: &AffineMat, _rnd: &mut Modnar, params: &[f32]) -> Point {
+        Point::new(p.x + params[0] * mat.e, p.y)
+    }
+}
+
+#[test]
+fn test_register_downstream_mutator() {
+    let params = vec![MutatorParam { name: String::from("k"), lower_bound: 0.0, upper_bound: 1.0, default_value: 0.5 }];
+    let mut registry = MutatorRegistry::global().write().unwrap();
+    let id = registry.free_id().unwrap();
+    // after the built-ins.
+    assert!(id > 98);
+
+    registry.register(Arc::new(Shift { id, params: params.clone() })).unwrap();
+    assert_eq!(registry.register(Arc::new(Shift { id: 0, params })), Err(MutatorError::IdTaken(0)));
+
+    let shift = registry.by_name("downstream_shift").unwrap();
+    assert_eq!(shift.id(), id);
+
+    let shifted = shift.apply(&Point::new(1.0, 2.0), &AffineMat::from(1.0, 0.0, 0.0, 1.0, 4.0, 0.0), &mut Modnar::new_rng_seeded(7), &[0.5]);
+    assert_eq!((shifted.x, shifted.y), (3.0, 2.0));
+}